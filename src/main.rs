@@ -45,8 +45,21 @@ cargo run --bin tiktok-cleaner -- --scan "/home/user/Pictures" --move
 */
 
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use clap::{Arg, Command};
-use tiktok_cleaner::tiktok_detection::{test_runner::TestRunner, scanner::TikTokScanner};
+use indicatif::{ProgressBar, ProgressStyle};
+use tiktok_cleaner::tiktok_detection::{
+    test_runner::TestRunner,
+    scanner::TikTokScanner,
+    report_export::{ExportFormat, ExportResults},
+    progress::{STAGE_CACHE_FILTERING, STAGE_PARALLEL_ANALYSIS, STAGE_FILE_ORGANIZATION},
+    file_util::scan_filter::ScanFilter,
+    file_util::file_manager::DeleteMethod,
+    file_util::action_runner::{ActionMode, ActionRunner, RetentionMode},
+    video_duplicate::VideoDuplicateDetector,
+    tiktok_video_det::TikTokVideoDetector,
+};
 
 /// Main entry point for the TikTok Detection and Organization Tool
 ///
@@ -78,15 +91,187 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .help("Run test experiment on built-in test sets")
                 .conflicts_with("scan")
         )
+        .arg(
+            Arg::new("train-model")
+                .long("train-model")
+                .value_names(["TIKTOK_DIR", "NOT_TIKTOK_DIR"])
+                .num_args(2)
+                .help("Train TikTokVideoDetector's logistic confidence model on labeled video folders (TikTok videos in TIKTOK_DIR, everything else in NOT_TIKTOK_DIR) and save it for future scans to load instead of the hand-tuned heuristic weights")
+                .conflicts_with_all(["scan", "test"])
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(["text", "json", "json-compact", "ndjson", "csv"])
+                .default_value("text")
+                .help("Output format for scan results: text, json, json-compact, ndjson, or csv")
+                .requires("scan")
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .value_name("FILE")
+                .help("Write the report to FILE instead of stdout")
+                .requires("scan")
+        )
+        .arg(
+            Arg::new("allowed-extensions")
+                .long("allowed-extensions")
+                .value_name("EXT,...")
+                .value_delimiter(',')
+                .help("Only scan files with these extensions or presets (VIDEO, IMAGE), e.g. jpg,png,mp4 or VIDEO; default is all supported media")
+                .requires("scan")
+        )
+        .arg(
+            Arg::new("excluded-extensions")
+                .long("excluded-extensions")
+                .value_name("EXT,...")
+                .value_delimiter(',')
+                .help("Skip files with these extensions or presets (VIDEO, IMAGE), even if otherwise allowed")
+                .requires("scan")
+        )
+        .arg(
+            Arg::new("excluded-dirs")
+                .long("excluded-dirs")
+                .value_name("PATTERN,...")
+                .value_delimiter(',')
+                .help("Skip directories matching these wildcard patterns (e.g. Android*,*WhatsApp*)")
+                .requires("scan")
+        )
+        .arg(
+            Arg::new("guide-formats")
+                .long("guide-formats")
+                .value_name("FORMAT,...")
+                .value_delimiter(',')
+                .value_parser(["json", "json-compact", "ndjson", "csv"])
+                .help("When the phone organization guide is written (MTP scans), also write it in these machine-readable formats alongside the markdown guide")
+                .requires("scan")
+        )
+        .arg(
+            Arg::new("min-video-size")
+                .long("min-video-size")
+                .value_name("BYTES")
+                .value_parser(clap::value_parser!(u64))
+                .help("Videos under this size are demoted out of the \"possible\" confidence tier unless other evidence confirms them (default: 51200)")
+                .requires("scan")
+        )
+        .arg(
+            Arg::new("delete-method")
+                .long("delete-method")
+                .value_name("METHOD")
+                .value_parser(["trash", "delete"])
+                .help("Dispose of detected files at or above --delete-threshold confidence instead of moving/copying them: \"trash\" sends them to the OS recycle bin, \"delete\" permanently removes them. Default is to leave DeleteMethod::None (organize only)")
+                .requires("scan")
+        )
+        .arg(
+            Arg::new("delete-threshold")
+                .long("delete-threshold")
+                .value_name("SCORE")
+                .value_parser(clap::value_parser!(u32))
+                .default_value("70")
+                .help("Minimum confidence score a file must meet before --delete-method disposes of it instead of organizing it")
+                .requires("delete-method")
+        )
+        .arg(
+            Arg::new("ruleset")
+                .long("ruleset")
+                .value_name("FILE")
+                .help("Load detection indicators and scoring rules from a TOML or JSON ruleset file instead of the built-in ruleset")
+                .requires("scan")
+        )
+        .arg(
+            Arg::new("dedup-retention")
+                .long("dedup-retention")
+                .value_name("MODE")
+                .value_parser(["keep-newest", "keep-oldest", "keep-largest"])
+                .help("Thin duplicate-video groups (see VideoDuplicateDetector) down to one survivor per group, chosen by this retention rule; every other group member is handled per --dedup-action")
+                .requires("scan")
+        )
+        .arg(
+            Arg::new("dedup-action")
+                .long("dedup-action")
+                .value_name("ACTION")
+                .value_parser(["dry-run", "quarantine", "trash", "delete"])
+                .default_value("dry-run")
+                .help("What to do with non-survivor files from --dedup-retention groups: \"dry-run\" only lists them, \"quarantine\" moves them into a timestamped folder with a restorable JSON manifest, \"trash\"/\"delete\" remove them outright")
+                .requires("dedup-retention")
+        )
+        .arg(
+            Arg::new("quarantine-threshold")
+                .long("quarantine-threshold")
+                .value_name("SCORE")
+                .value_parser(clap::value_parser!(u32))
+                .help("Instead of moving/copying, relocate every detected file at or above this confidence score into a single timestamped quarantine folder with a JSON manifest.json recording where each file came from, so the run can be undone with --restore-manifest")
+                .requires("scan")
+                .conflicts_with("delete-method")
+        )
+        .arg(
+            Arg::new("restore-manifest")
+                .long("restore-manifest")
+                .value_name("FILE")
+                .help("Undo a previous --quarantine-threshold run by moving every file recorded in this manifest.json back to its original location")
+                .requires("scan")
+        )
         .get_matches();
 
-    if matches.get_flag("test") {
+    if let Some(mut folders) = matches.get_many::<String>("train-model") {
+        let tiktok_folder = Path::new(folders.next().expect("num_args(2) guarantees two values"));
+        let not_tiktok_folder = Path::new(folders.next().expect("num_args(2) guarantees two values"));
+        let detector = TikTokVideoDetector::new()?;
+        detector.train_confidence_model(tiktok_folder, not_tiktok_folder)?;
+    } else if matches.get_flag("test") {
         // Run test experiment
         run_test_experiment()?;
+    } else if let (Some(scan_path), Some(manifest_path)) = (matches.get_one::<String>("scan"), matches.get_one::<String>("restore-manifest")) {
+        // Undo a previous --quarantine-threshold run instead of scanning.
+        let scanner = TikTokScanner::new(Path::new(scan_path))?;
+        let restored = scanner.restore_from_manifest(Path::new(manifest_path))?;
+        println!("↩️  Restored {} file(s) from {}", restored.len(), manifest_path);
     } else if let Some(scan_path) = matches.get_one::<String>("scan") {
         // Run scanner on specified folder
         let move_files = matches.get_flag("move");
-        run_scanner(scan_path, move_files)?;
+        let format = matches.get_one::<String>("format").map(String::as_str).unwrap_or("text");
+        let output = matches.get_one::<String>("output").map(String::as_str);
+        let allowed_extensions: Vec<String> = matches.get_many::<String>("allowed-extensions")
+            .map(|values| values.cloned().collect()).unwrap_or_default();
+        let excluded_extensions: Vec<String> = matches.get_many::<String>("excluded-extensions")
+            .map(|values| values.cloned().collect()).unwrap_or_default();
+        let excluded_dirs: Vec<String> = matches.get_many::<String>("excluded-dirs")
+            .map(|values| values.cloned().collect()).unwrap_or_default();
+        let guide_formats: Vec<ExportFormat> = matches.get_many::<String>("guide-formats")
+            .map(|values| values.map(|value| match value.as_str() {
+                "json" => ExportFormat::PrettyJson,
+                "json-compact" => ExportFormat::CompactJson,
+                "ndjson" => ExportFormat::Ndjson,
+                _ => ExportFormat::Csv,
+            }).collect())
+            .unwrap_or_default();
+        let min_video_size = matches.get_one::<u64>("min-video-size").copied();
+        let delete_method = match matches.get_one::<String>("delete-method").map(String::as_str) {
+            Some("trash") => DeleteMethod::MoveToTrash,
+            Some("delete") => DeleteMethod::Delete,
+            _ => DeleteMethod::None,
+        };
+        let delete_threshold = matches.get_one::<u32>("delete-threshold").copied().unwrap_or(70);
+        let ruleset = matches.get_one::<String>("ruleset").map(Path::new);
+        let dedup = matches.get_one::<String>("dedup-retention").map(String::as_str).map(|retention| {
+            let retention = match retention {
+                "keep-oldest" => RetentionMode::KeepOldest,
+                "keep-largest" => RetentionMode::KeepLargest,
+                _ => RetentionMode::KeepNewest,
+            };
+            let mode = match matches.get_one::<String>("dedup-action").map(String::as_str) {
+                Some("quarantine") => ActionMode::Quarantine,
+                Some("trash") => ActionMode::Trash,
+                Some("delete") => ActionMode::Delete,
+                _ => ActionMode::DryRun,
+            };
+            (retention, mode)
+        });
+        let quarantine_threshold = matches.get_one::<u32>("quarantine-threshold").copied();
+        let filter = ScanFilter::new(&allowed_extensions, &excluded_extensions, &excluded_dirs);
+        run_scanner(scan_path, move_files, format, output, filter, guide_formats, min_video_size, delete_method, delete_threshold, ruleset, dedup, quarantine_threshold)?;
     } else {
         // Show help if no arguments provided
         eprintln!("🚀 TikTok Detection and Organization Tool\n");
@@ -115,22 +300,55 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// # Arguments
 /// * `scan_path` - Path to the folder to scan (e.g., phone DCIM folder)
 /// * `move_files` - If true, actually moves detected files. If false, preview mode only.
+/// * `format` - One of `"text"`, `"json"`, `"json-compact"`, `"ndjson"`, or `"csv"`; controls how results are reported.
+/// * `output` - If set, the report is written to this file instead of stdout.
+/// * `filter` - Extension allow/deny list and excluded-directory patterns applied during the walk.
+/// * `guide_formats` - Extra machine-readable formats written alongside the markdown phone
+///   organization guide on MTP scans (empty means markdown only).
+/// * `min_video_size` - Overrides the minimum video size (bytes) a "possible"-tier video must
+///   meet to avoid being demoted to "unlikely". `None` keeps the scanner's default.
+/// * `delete_method` - Disposes of files at or above `delete_threshold` confidence via trash or
+///   permanent delete, instead of moving/copying them. `DeleteMethod::None` (the default) leaves
+///   the existing move/copy organization behavior untouched.
+/// * `delete_threshold` - Minimum confidence score a file must meet before `delete_method` applies.
+/// * `ruleset` - If set, detection indicators and scoring rules are loaded from this TOML or JSON
+///   file instead of the built-in ruleset.
+/// * `dedup` - If set, thins `VideoDuplicateDetector`-found duplicate-video groups down to one
+///   survivor per group (chosen by the `RetentionMode`) and applies the `ActionMode` to every
+///   other member, recording the result in an `ActionManifest`.
+/// * `quarantine_threshold` - If set, every file at or above this confidence score is moved into
+///   a single timestamped quarantine folder (see `FileManager::quarantine_file`) instead of
+///   being organized by `move_files`/`delete_method`, and recorded in that folder's
+///   `manifest.json` so the run can be undone with `TikTokScanner::restore_from_manifest`.
 ///
 /// # Examples
 /// ```
 /// // Preview scan without moving files
-/// run_scanner("/path/to/phone/DCIM", false)?;
-/// 
+/// run_scanner("/path/to/phone/DCIM", false, "text", None, ScanFilter::default(), Vec::new(), None, DeleteMethod::None, 70, None, None, None)?;
+///
 /// // Scan and organize detected TikTok files
-/// run_scanner("/path/to/phone/DCIM", true)?;
+/// run_scanner("/path/to/phone/DCIM", true, "text", None, ScanFilter::default(), Vec::new(), None, DeleteMethod::None, 70, None, None, None)?;
 /// ```
 ///
 /// # Phone Filesystem Support
 /// This function is designed to work with MTP-mounted Android phone storage:
 /// ```
-/// run_scanner("/run/user/1000/gvfs/mtp:host=SAMSUNG_*/Internal storage/DCIM", true)?;
+/// run_scanner("/run/user/1000/gvfs/mtp:host=SAMSUNG_*/Internal storage/DCIM", true, "text", None, ScanFilter::default(), Vec::new(), None, DeleteMethod::None, 70, None, None, None)?;
 /// ```
-fn run_scanner(scan_path: &str, move_files: bool) -> Result<(), Box<dyn std::error::Error>> {
+fn run_scanner(
+    scan_path: &str,
+    move_files: bool,
+    format: &str,
+    output: Option<&str>,
+    filter: ScanFilter,
+    guide_formats: Vec<ExportFormat>,
+    min_video_size: Option<u64>,
+    delete_method: DeleteMethod,
+    delete_threshold: u32,
+    ruleset: Option<&Path>,
+    dedup: Option<(RetentionMode, ActionMode)>,
+    quarantine_threshold: Option<u32>,
+) -> Result<(), Box<dyn std::error::Error>> {
     let path = Path::new(scan_path);
     
     if !path.exists() {
@@ -152,14 +370,134 @@ fn run_scanner(scan_path: &str, move_files: bool) -> Result<(), Box<dyn std::err
     }
     println!();
 
-    let mut scanner = TikTokScanner::new(path)?;
-    let results = scanner.scan_folder_parallel(move_files)?;
+    let mut scanner = TikTokScanner::new_with_filter_and_ruleset(path, filter, ruleset)?;
+    scanner.set_guide_export_formats(guide_formats);
+    if let Some(min_video_size) = min_video_size {
+        scanner.set_min_video_size_bytes(min_video_size);
+    }
+
+    // Ctrl-C flips this flag rather than killing the process outright, so a
+    // worker mid-file finishes that file (and any in-flight move) before the
+    // scan bails out - interrupting a large MTP scan shouldn't be able to
+    // leave a half-moved file behind.
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let ctrlc_flag = stop_flag.clone();
+    ctrlc::set_handler(move || {
+        eprintln!("\n⏹  Stopping after the current batch of files finishes...");
+        ctrlc_flag.store(true, Ordering::Relaxed);
+    })?;
+
+    let (progress_sender, progress_receiver) = crossbeam_channel::unbounded();
+    let progress_bar = ProgressBar::new(0);
+    progress_bar.set_style(
+        ProgressStyle::with_template("{msg} [{bar:40.cyan/blue}] {pos}/{len}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("=> "),
+    );
+    let progress_thread = std::thread::spawn(move || {
+        while let Ok(update) = progress_receiver.recv() {
+            let stage_name = match update.current_stage {
+                STAGE_CACHE_FILTERING => "Checking cache",
+                STAGE_PARALLEL_ANALYSIS => "Analyzing",
+                STAGE_FILE_ORGANIZATION => "Organizing",
+                _ => "Scanning",
+            };
+            progress_bar.set_length(update.files_to_check as u64);
+            progress_bar.set_position(update.files_checked as u64);
+            progress_bar.set_message(stage_name);
+        }
+        progress_bar.finish_and_clear();
+    });
+
+    let results = scanner.scan_folder_parallel_with_options(move_files, delete_method, delete_threshold, Some(progress_sender), Some(stop_flag))?;
+    let _ = progress_thread.join();
 
     if move_files && (results.confirmed_tiktok + results.likely_tiktok + results.possible_tiktok) > 0 {
         println!("\n✅ TikTok files have been organized into confidence-based folders!");
         println!("📁 Check the 'tiktok_detection' folder in your scan directory");
     }
 
+    if !results.deleted_files.is_empty() {
+        println!("\n🗑️  {} file(s) disposed of via --delete-method:", results.deleted_files.len());
+        for (path, reason) in &results.deleted_files {
+            println!("   - {}: {}", path, reason);
+        }
+    }
+
+    if let Some((retention, mode)) = dedup {
+        let duplicate_groups = VideoDuplicateDetector::new().find_duplicate_groups(&results.analyzed_files);
+        let action_runner = ActionRunner::new(path, ActionRunner::timestamped_quarantine_root(path));
+        let manifest = action_runner.run_on_duplicate_groups(&duplicate_groups, retention, mode)?;
+        if !manifest.records.is_empty() {
+            println!("\n🧹 {} duplicate file(s) handled via --dedup-retention/--dedup-action:", manifest.records.len());
+            for record in &manifest.records {
+                println!("   - {}: {}", record.source_path, record.reason);
+            }
+            let manifest_path = path.join("tiktok_detection").join(format!("dedup_manifest_{}.json",
+                std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)));
+            if let Some(parent) = manifest_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            manifest.save_to(&manifest_path)?;
+            println!("   Manifest saved to: {}", manifest_path.display());
+        }
+    }
+
+    if let Some(threshold) = quarantine_threshold {
+        let quarantined = scanner.quarantine_detected(&results.analyzed_files, threshold)?;
+        if quarantined > 0 {
+            println!("\n🗂️  {} file(s) quarantined via --quarantine-threshold; undo with --restore-manifest <manifest.json from the quarantine folder>", quarantined);
+        }
+    }
+
+    report_results(&results.analyzed_files, format, output)?;
+
+    Ok(())
+}
+
+/// Reports `analyzed_files` in the requested `format`, either to `output`
+/// (if given) or stdout. `"text"` with no `output` is a no-op, since
+/// `scan_folder_parallel` already printed a full human-readable summary as
+/// it ran; `"text"` with `output` writes a per-file listing (filename,
+/// verdict, confidence, evidence) to that file so it can be kept alongside
+/// a scan instead of only living in the terminal.
+fn report_results(analyzed_files: &[tiktok_cleaner::tiktok_detection::metadata_read::metadata_manager::FileMetadata], format: &str, output: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let export_format = match format {
+        "json" => Some(ExportFormat::PrettyJson),
+        "json-compact" => Some(ExportFormat::CompactJson),
+        "ndjson" => Some(ExportFormat::Ndjson),
+        "csv" => Some(ExportFormat::Csv),
+        _ => None,
+    };
+
+    match (export_format, output) {
+        (Some(export_format), Some(output_path)) => {
+            analyzed_files.export(Path::new(output_path), export_format)?;
+        }
+        (Some(ExportFormat::PrettyJson), None) => {
+            println!("{}", serde_json::to_string_pretty(&analyzed_files.analysis_records())?);
+        }
+        (Some(ExportFormat::Csv), None) => {
+            print!("{}", tiktok_cleaner::tiktok_detection::report_export::records_to_csv(&analyzed_files.analysis_records()));
+        }
+        (Some(ExportFormat::Ndjson), None) => {
+            print!("{}", tiktok_cleaner::tiktok_detection::report_export::records_to_ndjson(&analyzed_files.analysis_records())?);
+        }
+        (Some(_), None) => {
+            println!("{}", serde_json::to_string(&analyzed_files.analysis_records())?);
+        }
+        (None, Some(output_path)) => {
+            let mut text = String::new();
+            for record in analyzed_files.analysis_records() {
+                text.push_str(&format!("{} ({})\n  Confidence: {}/100\n  Evidence: {}\n\n",
+                    record.filename, record.verdict, record.confidence_score, record.evidence_found.join(", ")));
+            }
+            std::fs::write(output_path, text)?;
+            println!("📄 Scan report written to: {}", output_path);
+        }
+        (None, None) => {}
+    }
+
     Ok(())
 }
 
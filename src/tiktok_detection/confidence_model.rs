@@ -0,0 +1,198 @@
+use std::fs;
+use std::path::Path;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// A logistic-regression classifier over a fixed-width feature vector, plus
+/// the decision threshold picked for it. Replaces hand-tuned additive
+/// confidence scores with a calibrated probability: `p = sigmoid(w·x + b)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogisticModel {
+    pub weights: Vec<f64>,
+    pub bias: f64,
+    /// Probability cutoff, in `[0, 1]`, above which a file is classified
+    /// positive. Picked by maximizing Youden's J over a labeled validation
+    /// set rather than hardcoded.
+    pub threshold: f64,
+}
+
+impl LogisticModel {
+    /// Calibrated probability that `features` belongs to the positive class.
+    pub fn predict_proba(&self, features: &[f64]) -> f64 {
+        let z: f64 = self.weights.iter().zip(features).map(|(w, x)| w * x).sum::<f64>() + self.bias;
+        1.0 / (1.0 + (-z).exp())
+    }
+
+    /// Whether `features` clears `self.threshold`.
+    pub fn predict(&self, features: &[f64]) -> bool {
+        self.predict_proba(features) >= self.threshold
+    }
+
+    /// Fits `weights`/`bias` by batch gradient descent on log-loss with L2
+    /// regularization `l2`, then picks `threshold` by sweeping candidate
+    /// cutoffs over the same labeled `samples` and maximizing Youden's J
+    /// (sensitivity + specificity - 1). Intended for the small, hand-labeled
+    /// test folders this repo ships (hundreds of files, not millions), so a
+    /// few hundred full-batch epochs converge without mini-batching.
+    pub fn train(samples: &[(Vec<f64>, bool)], epochs: usize, learning_rate: f64, l2: f64) -> Self {
+        let feature_count = samples.first().map(|(features, _)| features.len()).unwrap_or(0);
+        let mut weights = vec![0.0; feature_count];
+        let mut bias = 0.0;
+
+        for _ in 0..epochs {
+            let mut weight_gradients = vec![0.0; feature_count];
+            let mut bias_gradient = 0.0;
+
+            for (features, label) in samples {
+                let z: f64 = weights.iter().zip(features).map(|(w, x)| w * x).sum::<f64>() + bias;
+                let prediction = 1.0 / (1.0 + (-z).exp());
+                let error = prediction - if *label { 1.0 } else { 0.0 };
+
+                for (gradient, x) in weight_gradients.iter_mut().zip(features) {
+                    *gradient += error * x;
+                }
+                bias_gradient += error;
+            }
+
+            let sample_count = samples.len().max(1) as f64;
+            for (weight, gradient) in weights.iter_mut().zip(&weight_gradients) {
+                *weight -= learning_rate * (gradient / sample_count + l2 * *weight);
+            }
+            bias -= learning_rate * bias_gradient / sample_count;
+        }
+
+        let mut model = LogisticModel { weights, bias, threshold: 0.5 };
+        model.threshold = model.best_threshold(samples);
+        model
+    }
+
+    /// Sweeps 101 candidate thresholds (0.00-1.00 in steps of 0.01) and
+    /// returns the one maximizing Youden's J = sensitivity + specificity - 1
+    /// over `samples`.
+    fn best_threshold(&self, samples: &[(Vec<f64>, bool)]) -> f64 {
+        let positives = samples.iter().filter(|(_, label)| *label).count().max(1) as f64;
+        let negatives = samples.iter().filter(|(_, label)| !*label).count().max(1) as f64;
+
+        let mut best_threshold = 0.5;
+        let mut best_j = f64::MIN;
+
+        for step in 0..=100 {
+            let threshold = step as f64 / 100.0;
+            let true_positives = samples.iter()
+                .filter(|(features, label)| *label && self.predict_proba(features) >= threshold)
+                .count() as f64;
+            let true_negatives = samples.iter()
+                .filter(|(features, label)| !*label && self.predict_proba(features) < threshold)
+                .count() as f64;
+
+            let youdens_j = (true_positives / positives) + (true_negatives / negatives) - 1.0;
+            if youdens_j > best_j {
+                best_j = youdens_j;
+                best_threshold = threshold;
+            }
+        }
+
+        best_threshold
+    }
+
+    /// Loads a previously trained/saved model from `path`, if present and
+    /// well-formed.
+    pub fn load_from(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Persists the model to `path` as pretty JSON, so it can be inspected,
+    /// diffed, or shipped alongside the binary.
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize confidence model")?;
+        fs::write(path, content).with_context(|| format!("Failed to write confidence model to {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn predict_proba_is_one_half_at_the_decision_boundary() {
+        let model = LogisticModel { weights: vec![1.0, -1.0], bias: 0.0, threshold: 0.5 };
+        assert!((model.predict_proba(&[0.0, 0.0]) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn predict_proba_increases_with_a_positively_weighted_feature() {
+        let model = LogisticModel { weights: vec![2.0], bias: 0.0, threshold: 0.5 };
+        assert!(model.predict_proba(&[5.0]) > model.predict_proba(&[0.0]));
+        assert!(model.predict_proba(&[5.0]) > 0.9);
+    }
+
+    #[test]
+    fn predict_respects_threshold() {
+        let model = LogisticModel { weights: vec![1.0], bias: 0.0, threshold: 0.9 };
+        // predict_proba(&[1.0]) is sigmoid(1.0) ~= 0.731, below the 0.9 threshold.
+        assert!(!model.predict(&[1.0]));
+        assert!(model.predict(&[10.0]));
+    }
+
+    #[test]
+    fn train_separates_a_linearly_separable_dataset() {
+        let samples = vec![
+            (vec![5.0], true),
+            (vec![4.0], true),
+            (vec![3.5], true),
+            (vec![-5.0], false),
+            (vec![-4.0], false),
+            (vec![-3.5], false),
+        ];
+
+        let model = LogisticModel::train(&samples, 500, 0.5, 0.0);
+
+        for (features, label) in &samples {
+            assert_eq!(model.predict(features), *label, "misclassified features {:?}", features);
+        }
+    }
+
+    #[test]
+    fn train_on_no_samples_produces_an_empty_model_without_panicking() {
+        let model = LogisticModel::train(&[], 10, 0.1, 0.01);
+        assert!(model.weights.is_empty());
+        assert_eq!(model.bias, 0.0);
+    }
+
+    #[test]
+    fn best_threshold_prefers_a_cutoff_that_separates_the_classes() {
+        let model = LogisticModel { weights: vec![1.0], bias: 0.0, threshold: 0.5 };
+        let samples = vec![
+            (vec![10.0], true),
+            (vec![8.0], true),
+            (vec![-10.0], false),
+            (vec![-8.0], false),
+        ];
+
+        let threshold = model.best_threshold(&samples);
+        for (features, label) in &samples {
+            assert_eq!(model.predict_proba(features) >= threshold, *label);
+        }
+    }
+
+    #[test]
+    fn save_to_and_load_from_round_trip_a_model() {
+        let model = LogisticModel { weights: vec![0.1, 0.2, 0.3], bias: -0.4, threshold: 0.55 };
+        let path = std::env::temp_dir().join(format!("confidence_model_test_{}.json", std::process::id()));
+
+        model.save_to(&path).unwrap();
+        let loaded = LogisticModel::load_from(&path).expect("a freshly saved model should load back");
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.weights, model.weights);
+        assert_eq!(loaded.bias, model.bias);
+        assert_eq!(loaded.threshold, model.threshold);
+    }
+
+    #[test]
+    fn load_from_returns_none_for_a_missing_file() {
+        let path = std::env::temp_dir().join(format!("confidence_model_test_missing_{}.json", std::process::id()));
+        assert!(LogisticModel::load_from(&path).is_none());
+    }
+}
@@ -0,0 +1,221 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use crate::tiktok_detection::metadata_read::metadata_manager::FileMetadata;
+
+/// How detected files at or above the confidence threshold are acted on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionMode {
+    /// List what would happen without touching any file. Default mode.
+    DryRun,
+    /// Relocate into a timestamped quarantine folder, preserving each
+    /// file's path relative to the scanned base directory.
+    Quarantine,
+    /// Send to the OS recycle bin via the `trash` crate.
+    Trash,
+    /// Permanently delete with `fs::remove_file`.
+    Delete,
+}
+
+/// Which copy of a duplicate group (see `VideoDuplicateDetector`) survives;
+/// every other member of the group is acted on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionMode {
+    KeepNewest,
+    KeepOldest,
+    KeepLargest,
+}
+
+/// One recorded action, so quarantines/deletions can be reviewed, audited,
+/// or undone later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionRecord {
+    pub source_path: String,
+    pub destination_path: Option<String>,
+    pub action: String,
+    pub confidence_score: u32,
+    pub reason: String,
+}
+
+/// The full set of actions from one `ActionRunner::run*` call, persisted as
+/// a JSON manifest.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ActionManifest {
+    pub records: Vec<ActionRecord>,
+}
+
+impl ActionManifest {
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize action manifest")?;
+        fs::write(path, content).with_context(|| format!("Failed to write action manifest to {}", path.display()))
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read action manifest from {}", path.display()))?;
+        serde_json::from_str(&content).context("Failed to parse action manifest")
+    }
+
+    /// Moves every quarantined file in the manifest back to its recorded
+    /// source path, undoing an `ActionMode::Quarantine` run. Trashed/deleted
+    /// files can't be restored through the manifest; `trash`-disposed files
+    /// still live in the OS recycle bin and must be restored from there.
+    pub fn restore(&self) -> Result<usize> {
+        let mut restored = 0;
+        for record in &self.records {
+            if record.action != "quarantined" {
+                continue;
+            }
+            if let Some(destination) = &record.destination_path {
+                if Path::new(destination).exists() {
+                    fs::rename(destination, &record.source_path)
+                        .with_context(|| format!("Failed to restore {} to {}", destination, record.source_path))?;
+                    restored += 1;
+                }
+            }
+        }
+        Ok(restored)
+    }
+}
+
+/// Runs a chosen `ActionMode` over detected files, optionally thinning
+/// `VideoDuplicateDetector`-style duplicate groups down to one survivor per
+/// group via `RetentionMode`, and recording every action taken in an
+/// `ActionManifest`.
+pub struct ActionRunner {
+    base_path: PathBuf,
+    quarantine_root: PathBuf,
+}
+
+impl ActionRunner {
+    /// `base_path` anchors the relative paths preserved inside the
+    /// quarantine folder; `quarantine_root` is where quarantined files are
+    /// relocated to (see `timestamped_quarantine_root`).
+    pub fn new(base_path: &Path, quarantine_root: PathBuf) -> Self {
+        ActionRunner { base_path: base_path.to_path_buf(), quarantine_root }
+    }
+
+    /// A fresh, timestamped quarantine folder under `base_path/tiktok_detection`,
+    /// so repeated runs don't collide and each run's quarantine is easy to
+    /// identify.
+    pub fn timestamped_quarantine_root(base_path: &Path) -> PathBuf {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        base_path.join("tiktok_detection").join(format!("quarantine_{}", timestamp))
+    }
+
+    /// Applies `mode` to every file in `results` whose confidence score is
+    /// at or above `confidence_threshold`. Returns the manifest of actions
+    /// taken (or that would be taken, in `ActionMode::DryRun`).
+    pub fn run(&self, results: &[FileMetadata], confidence_threshold: u32, mode: ActionMode) -> Result<ActionManifest> {
+        let mut manifest = ActionManifest::default();
+        for metadata in results {
+            if metadata.tiktok_analysis.confidence_score < confidence_threshold {
+                continue;
+            }
+            manifest.records.push(self.act_on(Path::new(&metadata.filepath), metadata.tiktok_analysis.confidence_score, mode)?);
+        }
+        Ok(manifest)
+    }
+
+    /// Same as `run`, but for `duplicate_groups` (e.g. from
+    /// `VideoDuplicateDetector::find_duplicate_groups`): within each group,
+    /// the file selected by `retention` survives untouched and every other
+    /// member is acted on via `mode`.
+    pub fn run_on_duplicate_groups(
+        &self,
+        duplicate_groups: &[Vec<FileMetadata>],
+        retention: RetentionMode,
+        mode: ActionMode,
+    ) -> Result<ActionManifest> {
+        let mut manifest = ActionManifest::default();
+        for group in duplicate_groups {
+            let Some(survivor_path) = Self::pick_survivor(group, retention) else { continue };
+            for metadata in group {
+                if metadata.filepath == survivor_path {
+                    continue;
+                }
+                manifest.records.push(self.act_on(Path::new(&metadata.filepath), metadata.tiktok_analysis.confidence_score, mode)?);
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Returns the filepath of the group member that should be kept, per
+    /// `retention`. "Newest"/"oldest" fall back to filesystem mtime, since
+    /// `FileMetadata` doesn't carry a modified-time field.
+    fn pick_survivor(group: &[FileMetadata], retention: RetentionMode) -> Option<String> {
+        match retention {
+            RetentionMode::KeepLargest => group.iter().max_by_key(|m| m.size_bytes).map(|m| m.filepath.clone()),
+            RetentionMode::KeepNewest | RetentionMode::KeepOldest => {
+                let mtimes: Vec<(&str, SystemTime)> = group.iter()
+                    .filter_map(|m| fs::metadata(&m.filepath).and_then(|meta| meta.modified()).ok().map(|t| (m.filepath.as_str(), t)))
+                    .collect();
+
+                let chosen = if retention == RetentionMode::KeepNewest {
+                    mtimes.iter().max_by_key(|(_, t)| *t)
+                } else {
+                    mtimes.iter().min_by_key(|(_, t)| *t)
+                };
+
+                chosen.map(|(path, _)| path.to_string())
+            }
+        }
+    }
+
+    fn act_on(&self, source_path: &Path, confidence: u32, mode: ActionMode) -> Result<ActionRecord> {
+        match mode {
+            ActionMode::DryRun => Ok(ActionRecord {
+                source_path: source_path.to_string_lossy().to_string(),
+                destination_path: None,
+                action: "would act (dry run)".to_string(),
+                confidence_score: confidence,
+                reason: format!("{}% confidence, no changes made", confidence),
+            }),
+            ActionMode::Quarantine => {
+                let destination = self.quarantine_destination(source_path);
+                if let Some(parent) = destination.parent() {
+                    fs::create_dir_all(parent).context("Failed to create quarantine subfolder")?;
+                }
+                fs::rename(source_path, &destination)
+                    .with_context(|| format!("Failed to quarantine {}", source_path.display()))?;
+                Ok(ActionRecord {
+                    source_path: source_path.to_string_lossy().to_string(),
+                    destination_path: Some(destination.to_string_lossy().to_string()),
+                    action: "quarantined".to_string(),
+                    confidence_score: confidence,
+                    reason: format!("moved to quarantine ({}% confidence)", confidence),
+                })
+            }
+            ActionMode::Trash => {
+                trash::delete(source_path).with_context(|| format!("Failed to move {} to trash", source_path.display()))?;
+                Ok(ActionRecord {
+                    source_path: source_path.to_string_lossy().to_string(),
+                    destination_path: None,
+                    action: "trashed".to_string(),
+                    confidence_score: confidence,
+                    reason: format!("moved to trash ({}% confidence)", confidence),
+                })
+            }
+            ActionMode::Delete => {
+                fs::remove_file(source_path).with_context(|| format!("Failed to delete {}", source_path.display()))?;
+                Ok(ActionRecord {
+                    source_path: source_path.to_string_lossy().to_string(),
+                    destination_path: None,
+                    action: "deleted".to_string(),
+                    confidence_score: confidence,
+                    reason: format!("permanently deleted ({}% confidence)", confidence),
+                })
+            }
+        }
+    }
+
+    /// Builds the quarantine destination for `source_path`: the quarantine
+    /// root joined with `source_path`'s path relative to `base_path` (or
+    /// just its filename if it isn't under `base_path`).
+    fn quarantine_destination(&self, source_path: &Path) -> PathBuf {
+        let relative = source_path.strip_prefix(&self.base_path).unwrap_or(source_path);
+        self.quarantine_root.join(relative)
+    }
+}
@@ -0,0 +1,40 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use anyhow::{Context, Result};
+
+/// Writes `content` to `path` without ever leaving a truncated file behind
+/// if the process is killed mid-write: the data lands in a sibling
+/// `<filename>.tmp.<pid>` file first, is flushed to disk, then atomically
+/// swapped into place with a single `rename` so readers always see either
+/// the old file or the complete new one, never a partial write. Falls back
+/// to a plain direct write if the temp-file-and-rename dance itself fails
+/// (e.g. an MTP mount that won't let us create extra files there), matching
+/// the previous best-effort behavior on those filesystems.
+pub fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    match write_atomic_via_tempfile(path, content) {
+        Ok(()) => Ok(()),
+        Err(_) => fs::write(path, content)
+            .with_context(|| format!("Failed to write {}", path.display())),
+    }
+}
+
+fn write_atomic_via_tempfile(path: &Path, content: &str) -> Result<()> {
+    let parent = path.parent().context("Target path has no parent directory")?;
+    let file_name = path.file_name().and_then(|n| n.to_str())
+        .context("Target path has no file name")?;
+    let temp_path = parent.join(format!("{}.tmp.{}", file_name, std::process::id()));
+
+    let mut temp_file = File::create(&temp_path)
+        .with_context(|| format!("Failed to create temp file {}", temp_path.display()))?;
+    temp_file.write_all(content.as_bytes())
+        .with_context(|| format!("Failed to write temp file {}", temp_path.display()))?;
+    temp_file.sync_all()
+        .with_context(|| format!("Failed to flush temp file {}", temp_path.display()))?;
+    drop(temp_file);
+
+    fs::rename(&temp_path, path)
+        .with_context(|| format!("Failed to rename {} into place at {}", temp_path.display(), path.display()))?;
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use anyhow::{Context, Result};
+
+/// Bytes sampled from the start and end of a file when computing a partial
+/// content hash. Large enough to catch most in-place edits without paying
+/// to read a multi-hundred-MB video in full.
+const SAMPLE_BYTES: u64 = 64 * 1024;
+
+/// Files at or under this size are hashed in full rather than sampled,
+/// since two seeks plus two reads would cost about as much as just reading
+/// the whole thing.
+const FULL_HASH_THRESHOLD: u64 = SAMPLE_BYTES * 2;
+
+/// Computes a fast content digest for `path`, used to confirm a cache
+/// entry's size+mtime match isn't a false "unchanged" verdict (an in-place
+/// edit that didn't change length, or a phone-restore that reset
+/// timestamps). Borrowed from Mercurial's `dirstate` status implementation:
+/// hash the whole file when it's small enough (or `force_full` is set),
+/// otherwise just the first and last `SAMPLE_BYTES` plus the total size, so
+/// a large video doesn't need a full read to be confirmed unchanged.
+pub fn partial_hash(path: &Path, force_full: bool) -> Result<String> {
+    let mut file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let size = file.metadata()
+        .with_context(|| format!("Failed to stat {}", path.display()))?
+        .len();
+
+    if force_full || size <= FULL_HASH_THRESHOLD {
+        let mut data = Vec::with_capacity(size as usize);
+        file.read_to_end(&mut data)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        return Ok(format!("{:x}", md5::compute(&data)));
+    }
+
+    let mut head = vec![0u8; SAMPLE_BYTES as usize];
+    file.read_exact(&mut head)
+        .with_context(|| format!("Failed to read head of {}", path.display()))?;
+
+    file.seek(SeekFrom::End(-(SAMPLE_BYTES as i64)))
+        .with_context(|| format!("Failed to seek tail of {}", path.display()))?;
+    let mut tail = vec![0u8; SAMPLE_BYTES as usize];
+    file.read_exact(&mut tail)
+        .with_context(|| format!("Failed to read tail of {}", path.display()))?;
+
+    let mut context = md5::Context::new();
+    context.consume(&head);
+    context.consume(&tail);
+    context.consume(size.to_le_bytes());
+
+    Ok(format!("{:x}", context.compute()))
+}
+
+/// Detects zero-length files and placeholder files whose content is all
+/// `0x00` (a common shape for partial downloads or pre-allocated-but-never-
+/// written files) — czkawka's empty-files check. Samples head and tail the
+/// same way `partial_hash` does rather than reading a whole large file, so a
+/// multi-hundred-MB zeroed file is still cheap to detect.
+pub fn is_zeroed_file(path: &Path) -> Result<bool> {
+    let mut file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let size = file.metadata()
+        .with_context(|| format!("Failed to stat {}", path.display()))?
+        .len();
+
+    if size == 0 {
+        return Ok(true);
+    }
+
+    if size <= FULL_HASH_THRESHOLD {
+        let mut data = Vec::with_capacity(size as usize);
+        file.read_to_end(&mut data)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        return Ok(data.iter().all(|&byte| byte == 0));
+    }
+
+    let mut head = vec![0u8; SAMPLE_BYTES as usize];
+    file.read_exact(&mut head)
+        .with_context(|| format!("Failed to read head of {}", path.display()))?;
+    if !head.iter().all(|&byte| byte == 0) {
+        return Ok(false);
+    }
+
+    file.seek(SeekFrom::End(-(SAMPLE_BYTES as i64)))
+        .with_context(|| format!("Failed to seek tail of {}", path.display()))?;
+    let mut tail = vec![0u8; SAMPLE_BYTES as usize];
+    file.read_exact(&mut tail)
+        .with_context(|| format!("Failed to read tail of {}", path.display()))?;
+
+    Ok(tail.iter().all(|&byte| byte == 0))
+}
@@ -3,6 +3,13 @@ use std::fs;
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use crate::tiktok_detection::file_util::path_safety;
+use crate::tiktok_detection::file_util::fs_schema_cache::{SchemaEntry, SharedFsSchemaCache};
+use crate::tiktok_detection::file_util::atomic_write::write_atomic;
+use crate::tiktok_detection::file_util::content_hash;
+use crate::tiktok_detection::metadata_read::metadata_manager::FileMetadata;
+use crate::tiktok_detection::video_duplicate::VideoDuplicateDetector;
+use crate::tiktok_detection::report_export::{ExportFormat, ExportResults};
 
 /// Cache structure for storing previously scanned files to avoid re-analysis
 /// 
@@ -28,12 +35,126 @@ pub struct FileInfo {
     pub size: u64,
     /// Last modification time as RFC3339 string
     pub modified: String,
+    /// Sub-second nanoseconds from `metadata.modified()`, when the platform
+    /// and filesystem provide them. `None` (or `Some(0)`, which a 1-second-
+    /// resolution filesystem reports just as often as a genuine zero) means
+    /// there's no sub-second precision to fall back on.
+    #[serde(default)]
+    pub modified_nanos: Option<u32>,
+    /// Set when this entry was recorded in the same wall-clock second as a
+    /// filesystem mtime with no usable sub-second precision - the dirstate-v2
+    /// "second-ambiguous" case, where an edit landing in that same second
+    /// would be indistinguishable from no edit at all. `should_skip_file`
+    /// always re-analyzes an ambiguous entry rather than trusting the
+    /// size/mtime match. Missing on old cache entries migrates to `true`
+    /// (re-analyze), the safe default.
+    #[serde(default = "default_ambiguous")]
+    pub ambiguous: bool,
+    /// Partial content digest (see `content_hash::partial_hash`), recorded
+    /// so an `ambiguous` entry can be confirmed unchanged by content rather
+    /// than trusted on size+mtime alone. `None` if hashing failed when the
+    /// entry was recorded.
+    #[serde(default)]
+    pub content_hash: Option<String>,
     /// Analysis result (confidence score)
     pub confidence: u32,
     /// Whether file was identified as TikTok content
     pub is_tiktok: bool,
 }
 
+fn default_ambiguous() -> bool {
+    true
+}
+
+/// Whether `path` looks like it lives on an MTP/phone mount (a gvfs mount or
+/// a user-session FUSE mount), where every read is a slow round-trip rather
+/// than a local syscall. Shared between `FileManager::new`'s folder/cache
+/// placement and anything else that needs to treat such paths differently.
+fn is_mtp_path(path: &Path) -> bool {
+    let path_str = path.to_string_lossy();
+    path_str.contains("gvfs/mtp") || path_str.contains("run/user")
+}
+
+/// Coarse classification of a filesystem entry, derived from its own
+/// `symlink_metadata` (i.e. without following a symlink to see what it
+/// points at). Borrowed from Mercurial's `BadType` tracking: a scan walk can
+/// turn up entries that aren't plain files — a symlink, a directory, or on
+/// gvfs/MTP mounts an odd pseudo-entry like a FIFO or socket — and those
+/// need to be recognized and skipped rather than fed into code that assumes
+/// `fs::metadata`/`File::open` on a regular file (opening a FIFO with no
+/// writer, for instance, would hang the scan rather than erroring).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    RegularFile,
+    Symlink,
+    Directory,
+    Fifo,
+    Socket,
+    BlockDevice,
+    CharDevice,
+    Unknown,
+}
+
+impl FileKind {
+    pub fn classify(path: &Path) -> Self {
+        let metadata = match fs::symlink_metadata(path) {
+            Ok(metadata) => metadata,
+            Err(_) => return FileKind::Unknown,
+        };
+        let file_type = metadata.file_type();
+
+        if file_type.is_symlink() {
+            return FileKind::Symlink;
+        }
+        if file_type.is_dir() {
+            return FileKind::Directory;
+        }
+        if file_type.is_file() {
+            return FileKind::RegularFile;
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+            if file_type.is_fifo() {
+                return FileKind::Fifo;
+            }
+            if file_type.is_socket() {
+                return FileKind::Socket;
+            }
+            if file_type.is_block_device() {
+                return FileKind::BlockDevice;
+            }
+            if file_type.is_char_device() {
+                return FileKind::CharDevice;
+            }
+        }
+
+        FileKind::Unknown
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FileKind::RegularFile => "regular file",
+            FileKind::Symlink => "symlink",
+            FileKind::Directory => "directory",
+            FileKind::Fifo => "FIFO",
+            FileKind::Socket => "socket",
+            FileKind::BlockDevice => "block device",
+            FileKind::CharDevice => "character device",
+            FileKind::Unknown => "unknown entry type",
+        }
+    }
+}
+
+/// Result of a `NotTikTokCache` prune pass: how many stale entries were
+/// dropped and how many bytes of (no longer present) files they recorded.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PruneSummary {
+    pub removed_entries: usize,
+    pub removed_bytes: u64,
+}
+
 impl NotTikTokCache {
     /// Creates a new empty cache with current timestamp
     pub fn new() -> Self {
@@ -41,7 +162,32 @@ impl NotTikTokCache {
             scanned_files: Vec::new(),
             last_updated: chrono::Utc::now().to_rfc3339(),
             file_metadata: HashMap::new(),
-            cache_version: "2.0".to_string(),
+            cache_version: "2.2".to_string(),
+        }
+    }
+
+    /// Drops every `file_metadata` entry (and the matching `scanned_files`
+    /// name) whose path no longer exists on disk — deleted, moved, or
+    /// belonging to a phone that isn't connected right now. Only ever grows
+    /// otherwise, so a library scanned repeatedly over time would keep every
+    /// entry it ever saw without this.
+    fn prune_missing(&mut self) -> PruneSummary {
+        let missing: Vec<(String, u64)> = self.file_metadata.iter()
+            .filter(|(path, _)| !Path::new(path).exists())
+            .map(|(path, info)| (path.clone(), info.size))
+            .collect();
+
+        let missing_paths: std::collections::HashSet<&str> =
+            missing.iter().map(|(path, _)| path.as_str()).collect();
+
+        for (path, _) in &missing {
+            self.file_metadata.remove(path);
+        }
+        self.scanned_files.retain(|path| !missing_paths.contains(path.as_str()));
+
+        PruneSummary {
+            removed_entries: missing.len(),
+            removed_bytes: missing.iter().map(|(_, size)| size).sum(),
         }
     }
 
@@ -57,51 +203,82 @@ impl NotTikTokCache {
     }
 
     /// Checks if a file should be skipped based on cache and modification time
-    /// 
+    ///
+    /// An entry flagged `ambiguous` can't be trusted on size+mtime alone, so
+    /// this falls back to recomputing a partial content hash and comparing
+    /// it against the one stored when the entry was added - catching both
+    /// the same-second-mtime race and a phone-restore that resets
+    /// timestamps without touching content.
+    ///
     /// # Arguments
     /// * `file_path` - Path to the file
     /// * `current_size` - Current file size
     /// * `current_modified` - Current modification time
-    /// 
+    /// * `force_full_hash` - Hash the whole file instead of sampling, when a content-hash check is needed
+    ///
     /// # Returns
     /// `true` if the file can be skipped, `false` if it needs re-analysis
-    pub fn should_skip_file(&self, file_path: &Path, current_size: u64, current_modified: &str) -> bool {
+    pub fn should_skip_file(&self, file_path: &Path, current_size: u64, current_modified: &str, force_full_hash: bool) -> bool {
         let path_str = file_path.to_string_lossy().to_string();
-        
-        if let Some(cached_info) = self.file_metadata.get(&path_str) {
-            // Skip if file hasn't changed and was previously identified as non-TikTok
-            cached_info.size == current_size && 
-            cached_info.modified == current_modified &&
-            !cached_info.is_tiktok
-        } else {
-            false
+
+        let cached_info = match self.file_metadata.get(&path_str) {
+            Some(cached_info) => cached_info,
+            None => return false,
+        };
+
+        if cached_info.is_tiktok || cached_info.size != current_size || cached_info.modified != current_modified {
+            return false;
+        }
+
+        if !cached_info.ambiguous {
+            return true;
+        }
+
+        match (&cached_info.content_hash, content_hash::partial_hash(file_path, force_full_hash)) {
+            (Some(stored_hash), Ok(current_hash)) => *stored_hash == current_hash,
+            _ => false,
         }
     }
 
     /// Adds a file to the cache with its analysis results
-    /// 
+    ///
     /// # Arguments
     /// * `file_path` - Path to the file
     /// * `size` - File size in bytes
     /// * `modified` - File modification time
+    /// * `modified_nanos` - Sub-second nanoseconds from `metadata.modified()`, if the platform provides them
+    /// * `content_hash` - Partial content digest (see `content_hash::partial_hash`), used to confirm an ambiguous entry on a later scan
     /// * `confidence` - TikTok detection confidence score
     /// * `is_tiktok` - Whether the file was identified as TikTok content
-    pub fn add_file_with_metadata(&mut self, file_path: &str, size: u64, modified: String, confidence: u32, is_tiktok: bool) {
+    pub fn add_file_with_metadata(&mut self, file_path: &str, size: u64, modified: String, modified_nanos: Option<u32>, content_hash: Option<String>, confidence: u32, is_tiktok: bool) {
+        let now = chrono::Utc::now();
+
+        // Same-second comparison uses whole seconds only, mirroring the
+        // one-second resolution most filesystem mtimes actually have.
+        let file_secs = chrono::DateTime::parse_from_rfc3339(&modified)
+            .map(|dt| dt.timestamp())
+            .unwrap_or_else(|_| now.timestamp());
+        let has_subsecond_precision = modified_nanos.unwrap_or(0) != 0;
+        let ambiguous = file_secs == now.timestamp() && !has_subsecond_precision;
+
         let file_info = FileInfo {
             size,
             modified,
+            modified_nanos,
+            ambiguous,
+            content_hash,
             confidence,
             is_tiktok,
         };
-        
+
         self.file_metadata.insert(file_path.to_string(), file_info);
-        
+
         // Only add to scanned_files if it's not TikTok content (for backward compatibility)
         if !is_tiktok && !self.contains(file_path) {
             self.scanned_files.push(file_path.to_string());
         }
-        
-        self.last_updated = chrono::Utc::now().to_rfc3339();
+
+        self.last_updated = now.to_rfc3339();
     }
 
     /// Legacy method for backward compatibility
@@ -132,6 +309,22 @@ impl NotTikTokCache {
                         cache.cache_version = "2.0".to_string();
                         println!("📱 Migrated cache to version 2.0");
                     }
+                    if cache.cache_version == "2.0" {
+                        // Entries written before ambiguous-mtime tracking
+                        // existed already defaulted to `ambiguous: true` via
+                        // serde, so there's nothing to backfill here beyond
+                        // recording the version bump.
+                        cache.cache_version = "2.1".to_string();
+                        println!("📱 Migrated cache to version 2.1 (ambiguous-mtime detection)");
+                    }
+                    if cache.cache_version == "2.1" {
+                        // Same story: entries from before content-hash
+                        // confirmation existed default to `content_hash:
+                        // None` via serde, which `should_skip_file` already
+                        // treats as "can't confirm, re-analyze".
+                        cache.cache_version = "2.2".to_string();
+                        println!("📱 Migrated cache to version 2.2 (content-hash confirmation)");
+                    }
                     Ok(cache)
                 },
                 Err(_) => {
@@ -149,7 +342,7 @@ impl NotTikTokCache {
                                 scanned_files: legacy.scanned_files,
                                 last_updated: legacy.last_updated,
                                 file_metadata: HashMap::new(),
-                                cache_version: "2.0".to_string(),
+                                cache_version: "2.2".to_string(),
                             })
                         },
                         Err(e) => {
@@ -167,12 +360,61 @@ impl NotTikTokCache {
     pub fn save_to_file(&self, json_path: &Path) -> Result<()> {
         let content = serde_json::to_string_pretty(self)
             .context("Failed to serialize cache")?;
-        fs::write(json_path, content)
-            .context("Failed to write not_tiktok.json")?;
-        Ok(())
+        // Write via a temp-file-and-rename so a process killed mid-write
+        // (common on long phone scans) never leaves a truncated
+        // not_tiktok.json behind for the next run to trip over.
+        write_atomic(json_path, &content)
     }
 }
 
+/// How a detected file should be disposed of once it clears the
+/// configured confidence threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteMethod {
+    /// Leave the file alone (the existing move/copy organization behavior).
+    None,
+    /// Send the file to the OS recycle bin via the `trash` crate, rather
+    /// than hard-unlinking it.
+    MoveToTrash,
+    /// Permanently delete the file with `fs::remove_file`.
+    Delete,
+}
+
+/// How a detected file should ultimately be disposed of, modeled on
+/// Czkawka's `DeleteMethod` but widened past plain move/copy to also cover a
+/// reversible quarantine flow and a delete that degrades gracefully when the
+/// OS trash isn't available (e.g. an MTP mount).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disposition {
+    /// Move into a confidence subfolder under the tiktok_detection folder
+    /// (the existing `move_file_to_tiktok_folder` behavior).
+    OrganizeMove,
+    /// Copy into a confidence subfolder, leaving the original in place (the
+    /// existing `copy_file_to_tiktok_folder` preview behavior).
+    OrganizeCopy,
+    /// Move into a single timestamped quarantine directory and record the
+    /// move in a JSON manifest, so the operation can be fully undone with
+    /// `restore_from_manifest`.
+    Quarantine,
+    /// Remove the file: OS trash when available, falling back to
+    /// `Quarantine` rather than an unrecoverable hard delete when trashing
+    /// isn't permitted.
+    Delete,
+}
+
+/// One entry in a quarantine directory's `manifest.json`, recording enough
+/// to reverse the move: where the file came from, where it ended up, and
+/// the metadata it had at the time, so `restore_from_manifest` doesn't need
+/// to guess.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    pub original_path: String,
+    pub quarantine_path: String,
+    pub size: u64,
+    pub modified: String,
+    pub confidence: u32,
+}
+
 /// File management system for TikTok detection and organization
 /// 
 /// This struct handles all file operations including:
@@ -189,6 +431,20 @@ pub struct FileManager {
     cache_file: PathBuf,
     /// In-memory cache for file analysis results
     cache: NotTikTokCache,
+    /// Cached directory listings keyed by path + mtime, so a repeat scan of
+    /// an unchanged subtree can skip re-reading it entirely.
+    fs_schema_cache: SharedFsSchemaCache,
+    /// When set, content hashing (see `content_hash::partial_hash`) always
+    /// reads the whole file instead of sampling, regardless of size. Off by
+    /// default since sampling is already exact for anything at or under
+    /// `content_hash`'s full-hash threshold, so the flag only changes how
+    /// files above that threshold are hashed.
+    force_full_content_hash: bool,
+    /// Lazily-created, timestamped quarantine directory for this run. Kept
+    /// so repeated `Disposition::Quarantine`/`Disposition::Delete` fallbacks
+    /// within one run land in the same directory instead of a fresh one per
+    /// file.
+    quarantine_dir: Option<PathBuf>,
 }
 
 impl FileManager {
@@ -209,11 +465,10 @@ impl FileManager {
     /// Returns error if folder creation fails or cache cannot be loaded
     pub fn new(base_path: &Path) -> Result<Self> {
         let base_path = base_path.to_path_buf();
-        
+
         // Check if we're dealing with an MTP/phone filesystem
-        let is_mtp_path = base_path.to_string_lossy().contains("gvfs/mtp") || 
-                          base_path.to_string_lossy().contains("run/user");
-        
+        let is_mtp_path = is_mtp_path(&base_path);
+
         let (tiktok_folder, cache_file) = if is_mtp_path {
             // For MTP/phone paths, try to create folder on phone first, fallback to local
             let phone_tiktok_folder = base_path.join("tiktok_detection");
@@ -269,22 +524,241 @@ impl FileManager {
             NotTikTokCache::new()
         });
 
+        // The FS-schema cache lives next to the not-TikTok cache (same
+        // phone-vs-local fallback applies, since it's just as unwritable on
+        // a stubborn MTP mount), under a sibling filename.
+        let fs_schema_cache_file = cache_file.with_file_name("fs_schema.json");
+        let fs_schema_cache = SharedFsSchemaCache::load(fs_schema_cache_file);
+
         Ok(FileManager {
             base_path,
             tiktok_folder,
             cache_file,
             cache,
+            fs_schema_cache,
+            force_full_content_hash: false,
+            quarantine_dir: None,
         })
     }
 
+    /// Forces content-hash confirmation (see `content_hash::partial_hash`)
+    /// to read the whole file instead of sampling the start/end, regardless
+    /// of size. Files at or under `content_hash`'s full-hash threshold are
+    /// read whole either way, so only larger files are affected.
+    pub fn set_force_full_content_hash(&mut self, force: bool) {
+        self.force_full_content_hash = force;
+    }
+
+    /// Prunes `NotTikTokCache` entries whose path no longer exists, then
+    /// saves the result. Skipped by default on a detected MTP/phone path,
+    /// since a simply-disconnected phone looks identical to "every file
+    /// vanished" and would otherwise wipe out entries that are still
+    /// perfectly valid once it's reconnected; pass `force = true` to prune
+    /// anyway (e.g. when the caller knows the phone is actually connected).
+    pub fn compact_cache(&mut self, force: bool) -> Result<PruneSummary> {
+        if self.is_mtp_path() && !force {
+            println!("Skipping cache compaction on MTP/phone path (call with force = true to override)");
+            return Ok(PruneSummary::default());
+        }
+
+        let summary = self.cache.prune_missing();
+        if summary.removed_entries > 0 {
+            println!("Pruned {} stale cache entries ({} bytes)", summary.removed_entries, summary.removed_bytes);
+        }
+        self.save_cache()?;
+
+        Ok(summary)
+    }
+
+    /// Removes `source_path` according to `method`, recording the action
+    /// with `reason` for the caller to report. Only called for files at or
+    /// above the confidence threshold the caller has already checked.
+    ///
+    /// Returns `Ok(Some(reason))` on success (for `ScanResults::deleted_files`),
+    /// or `Ok(None)` when `method` is `DeleteMethod::None` (no-op).
+    pub fn dispose_file(&self, source_path: &Path, method: DeleteMethod, confidence: u32) -> Result<Option<String>> {
+        match method {
+            DeleteMethod::None => Ok(None),
+            DeleteMethod::MoveToTrash => {
+                trash::delete(source_path)
+                    .with_context(|| format!("Failed to move {} to trash", source_path.display()))?;
+                Ok(Some(format!("moved to trash ({}% confidence)", confidence)))
+            }
+            DeleteMethod::Delete => {
+                fs::remove_file(source_path)
+                    .with_context(|| format!("Failed to delete {}", source_path.display()))?;
+                Ok(Some(format!("deleted ({}% confidence)", confidence)))
+            }
+        }
+    }
+
+    /// Disposes of `source_path` according to `disposition`, recording the
+    /// action taken for the caller to report. Unlike `dispose_file`, every
+    /// variant here has somewhere for the file to land: `Disposition::Delete`
+    /// itself never throws the file away unrecoverably unless the OS trash
+    /// accepted it.
+    pub fn dispose_with(&mut self, source_path: &Path, disposition: Disposition, confidence: u32) -> Result<Option<String>> {
+        match disposition {
+            Disposition::OrganizeMove => {
+                let target = self.move_file_to_tiktok_folder(source_path, confidence)?;
+                Ok(Some(format!("moved to {} ({}% confidence)", target.display(), confidence)))
+            }
+            Disposition::OrganizeCopy => {
+                let target = self.copy_file_to_tiktok_folder(source_path, confidence)?;
+                Ok(Some(format!("copied to {} ({}% confidence)", target.display(), confidence)))
+            }
+            Disposition::Quarantine => {
+                let target = self.quarantine_file(source_path, confidence)?;
+                Ok(Some(format!("quarantined to {} ({}% confidence)", target.display(), confidence)))
+            }
+            Disposition::Delete => {
+                match trash::delete(source_path) {
+                    Ok(_) => Ok(Some(format!("moved to trash ({}% confidence)", confidence))),
+                    Err(_) => {
+                        // Trashing isn't permitted here (e.g. an MTP mount);
+                        // fall back to the reversible quarantine flow rather
+                        // than losing the file outright.
+                        let target = self.quarantine_file(source_path, confidence)?;
+                        Ok(Some(format!("trash unavailable, quarantined to {} ({}% confidence)", target.display(), confidence)))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns this run's quarantine directory, creating it (named with the
+    /// run's start time) on first use so every quarantined file in the same
+    /// run lands in one place.
+    fn quarantine_dir(&mut self) -> Result<PathBuf> {
+        if let Some(dir) = &self.quarantine_dir {
+            return Ok(dir.clone());
+        }
+
+        let dir_name = format!("quarantine_{}", chrono::Utc::now().format("%Y%m%dT%H%M%SZ"));
+        let dir = self.tiktok_folder.join(dir_name);
+        fs::create_dir_all(&dir).context("Failed to create quarantine directory")?;
+
+        self.quarantine_dir = Some(dir.clone());
+        Ok(dir)
+    }
+
+    /// Moves `source_path` into this run's quarantine directory and appends
+    /// a `QuarantineRecord` to its `manifest.json`, so the move can be
+    /// reversed later with `restore_from_manifest`.
+    pub fn quarantine_file(&mut self, source_path: &Path, confidence: u32) -> Result<PathBuf> {
+        if let Some(reason) = Self::bad_entry_reason(source_path) {
+            anyhow::bail!("Refusing to quarantine non-regular file: {}", reason);
+        }
+
+        let filename = source_path.file_name().context("Invalid file name")?;
+        let metadata = fs::metadata(source_path).context("Failed to read file metadata")?;
+        let size = metadata.len();
+        let modified = metadata.modified()
+            .context("Failed to get modification time")?
+            .duration_since(std::time::UNIX_EPOCH)
+            .context("Invalid modification time")?;
+        let modified_str = chrono::DateTime::<chrono::Utc>::from(
+            std::time::UNIX_EPOCH + modified
+        ).to_rfc3339();
+
+        let quarantine_dir = self.quarantine_dir()?;
+        let target_path = path_safety::safe_join(&quarantine_dir, filename)?;
+        let final_target = self.resolve_filename_conflict(&target_path)?;
+
+        match fs::rename(source_path, &final_target) {
+            Ok(_) => {}
+            Err(_) => {
+                // Same cross-filesystem fallback as `move_file_to_tiktok_folder`.
+                fs::copy(source_path, &final_target)
+                    .with_context(|| format!("Failed to quarantine {}", source_path.display()))?;
+                fs::remove_file(source_path).ok();
+            }
+        }
+
+        let record = QuarantineRecord {
+            original_path: source_path.to_string_lossy().to_string(),
+            quarantine_path: final_target.to_string_lossy().to_string(),
+            size,
+            modified: modified_str,
+            confidence,
+        };
+        self.append_quarantine_record(&quarantine_dir, record)?;
+
+        Ok(final_target)
+    }
+
+    fn append_quarantine_record(&self, quarantine_dir: &Path, record: QuarantineRecord) -> Result<()> {
+        let manifest_path = quarantine_dir.join("manifest.json");
+
+        let mut records: Vec<QuarantineRecord> = fs::read_to_string(&manifest_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        records.push(record);
+
+        let content = serde_json::to_string_pretty(&records)
+            .context("Failed to serialize quarantine manifest")?;
+        write_atomic(&manifest_path, &content)
+    }
+
+    /// Restores every file recorded in `manifest_path` (as written by
+    /// `quarantine_file`) back to its original location, resolving name
+    /// conflicts the same way `move_file_to_tiktok_folder` does. Returns the
+    /// path each file was actually restored to.
+    pub fn restore_from_manifest(&self, manifest_path: &Path) -> Result<Vec<String>> {
+        let content = fs::read_to_string(manifest_path)
+            .with_context(|| format!("Failed to read quarantine manifest {}", manifest_path.display()))?;
+        let records: Vec<QuarantineRecord> = serde_json::from_str(&content)
+            .context("Failed to parse quarantine manifest")?;
+
+        let mut restored = Vec::new();
+        for record in &records {
+            let quarantine_path = Path::new(&record.quarantine_path);
+            if !quarantine_path.exists() {
+                eprintln!("WARNING: Quarantined file missing, skipping restore: {}", record.quarantine_path);
+                continue;
+            }
+
+            let original_path = Path::new(&record.original_path);
+            let restore_target = self.resolve_filename_conflict(original_path)?;
+
+            if let Some(parent) = restore_target.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to recreate original directory {}", parent.display()))?;
+            }
+
+            match fs::rename(quarantine_path, &restore_target) {
+                Ok(_) => {}
+                Err(_) => {
+                    fs::copy(quarantine_path, &restore_target)
+                        .with_context(|| format!("Failed to restore {} to {}", record.quarantine_path, restore_target.display()))?;
+                    fs::remove_file(quarantine_path).ok();
+                }
+            }
+
+            restored.push(restore_target.to_string_lossy().to_string());
+        }
+
+        Ok(restored)
+    }
+
     pub fn move_file_to_tiktok_folder(&mut self, source_path: &Path, confidence: u32) -> Result<PathBuf> {
+        if let Some(reason) = Self::bad_entry_reason(source_path) {
+            anyhow::bail!("Refusing to move non-regular file: {}", reason);
+        }
+
         let filename = source_path.file_name()
             .context("Invalid file name")?;
-        
+
+        // Bail out on a symlink chain that's still a symlink after
+        // `MAX_SYMLINK_HOPS`, rather than letting `fs::rename` hang or loop
+        // on a maliciously/accidentally cyclic source path.
+        path_safety::resolve_symlink_bounded(source_path)?;
+
         // Create confidence-based subfolder
         let subfolder = match confidence {
             70.. => "confirmed",
-            40..=69 => "likely", 
+            40..=69 => "likely",
             20..=39 => "possible",
             _ => "unlikely",
         };
@@ -295,9 +769,14 @@ impl FileManager {
                 .context("Failed to create confidence subfolder")?;
         }
 
-        let target_path = target_folder.join(filename);
-        
-        // Handle file name conflicts
+        // Sanitizes `filename` and asserts the resolved destination stays
+        // inside `target_folder`, so a hostile filename (`../../etc/...`, an
+        // absolute path, embedded separators) can't relocate the move
+        // outside the intended organization folder.
+        let target_path = path_safety::safe_join(&target_folder, filename)?;
+
+        // Handle file name conflicts with a deterministic unique suffix
+        // instead of overwriting.
         let final_target = self.resolve_filename_conflict(&target_path)?;
 
         // Try to move the file, but handle MTP/phone filesystem errors gracefully
@@ -370,8 +849,12 @@ impl FileManager {
     /// * `confidence` - Detection confidence score (0-100+)
     /// * `is_tiktok` - Whether file was identified as TikTok content
     pub fn add_to_cache(&mut self, file_path: &Path, confidence: u32, is_tiktok: bool) -> Result<()> {
+        if let Some(reason) = Self::bad_entry_reason(file_path) {
+            anyhow::bail!("Refusing to cache non-regular file: {}", reason);
+        }
+
         let path_str = file_path.to_string_lossy().to_string();
-        
+
         // Get file metadata
         let metadata = fs::metadata(file_path).context("Failed to read file metadata")?;
         let size = metadata.len();
@@ -382,8 +865,10 @@ impl FileManager {
         let modified_str = chrono::DateTime::<chrono::Utc>::from(
             std::time::UNIX_EPOCH + modified
         ).to_rfc3339();
+        let modified_nanos = Some(modified.subsec_nanos());
+        let content_hash = content_hash::partial_hash(file_path, self.force_full_content_hash).ok();
 
-        self.cache.add_file_with_metadata(&path_str, size, modified_str, confidence, is_tiktok);
+        self.cache.add_file_with_metadata(&path_str, size, modified_str, modified_nanos, content_hash, confidence, is_tiktok);
         
         // Try to save cache, but don't fail the operation if it's not possible
         let _ = self.save_cache();
@@ -408,8 +893,23 @@ impl FileManager {
             std::time::UNIX_EPOCH + modified
         ).to_rfc3339();
 
-        let should_skip = self.cache.should_skip_file(file_path, size, &modified_str);
-        
+        self.should_skip_entry(file_path, size, &modified_str)
+    }
+
+    /// Same as `should_skip_file`, but takes a size/modified-time pair the
+    /// caller already has on hand (e.g. from a directory walk) instead of
+    /// stat-ing the file again. Worthwhile on slow gvfs/mtp-mounted phone
+    /// filesystems where every extra stat is a round-trip.
+    ///
+    /// # Returns
+    /// `(should_skip, cached_confidence)` - Whether to skip and previous confidence if available
+    pub fn should_skip_entry(&self, file_path: &Path, size: u64, modified_str: &str) -> Result<(bool, Option<u32>)> {
+        if let Some(reason) = Self::bad_entry_reason(file_path) {
+            anyhow::bail!("Refusing to check cache for non-regular file: {}", reason);
+        }
+
+        let should_skip = self.cache.should_skip_file(file_path, size, modified_str, self.force_full_content_hash);
+
         if should_skip {
             // Get cached confidence if available
             let path_str = file_path.to_string_lossy().to_string();
@@ -441,6 +941,20 @@ impl FileManager {
         (self.cache.scanned_files.len(), &self.cache.last_updated)
     }
 
+    /// Returns `dir`'s entries, reusing the cached listing from a previous
+    /// scan when `dir` itself hasn't changed. Safe to call concurrently from
+    /// rayon worker threads walking different subtrees.
+    pub fn list_dir_cached(&self, dir: &Path) -> Result<Vec<SchemaEntry>> {
+        self.fs_schema_cache.list_dir(dir)
+    }
+
+    pub fn save_fs_schema_cache(&self) -> Result<()> {
+        // Same best-effort handling as `save_cache`: a phone filesystem that
+        // can't persist it just re-scans from scratch next time.
+        let _ = self.fs_schema_cache.save();
+        Ok(())
+    }
+
     fn resolve_filename_conflict(&self, target_path: &Path) -> Result<PathBuf> {
         if !target_path.exists() {
             return Ok(target_path.to_path_buf());
@@ -464,12 +978,18 @@ impl FileManager {
     }
 
     pub fn copy_file_to_tiktok_folder(&self, source_path: &Path, confidence: u32) -> Result<PathBuf> {
+        if let Some(reason) = Self::bad_entry_reason(source_path) {
+            anyhow::bail!("Refusing to copy non-regular file: {}", reason);
+        }
+
         let filename = source_path.file_name()
             .context("Invalid file name")?;
-        
+
+        path_safety::resolve_symlink_bounded(source_path)?;
+
         let subfolder = match confidence {
             70.. => "confirmed",
-            40..=69 => "likely", 
+            40..=69 => "likely",
             20..=39 => "possible",
             _ => "unlikely",
         };
@@ -480,7 +1000,7 @@ impl FileManager {
                 .context("Failed to create confidence subfolder")?;
         }
 
-        let target_path = target_folder.join(filename);
+        let target_path = path_safety::safe_join(&target_folder, filename)?;
         let final_target = self.resolve_filename_conflict(&target_path)?;
 
         // Try to copy the file, but handle MTP/phone filesystem errors gracefully
@@ -524,6 +1044,41 @@ impl FileManager {
         &self.base_path
     }
 
+    /// Returns `Some(reason)` if `path` isn't safe to treat as a regular
+    /// file for analysis, caching, or move/copy: a plain symlink is followed
+    /// (up to `MAX_SYMLINK_HOPS`) and allowed through if it resolves to a
+    /// regular file, but a directory, FIFO, socket, device node, or anything
+    /// `symlink_metadata` couldn't classify is rejected outright. Callers
+    /// that would otherwise stat/open/rename `path` should check this first
+    /// and skip with the returned reason instead.
+    pub fn bad_entry_reason(path: &Path) -> Option<String> {
+        let kind = FileKind::classify(path);
+
+        let kind = if kind == FileKind::Symlink {
+            match path_safety::resolve_symlink_bounded(path) {
+                Ok(resolved) if FileKind::classify(&resolved) == FileKind::RegularFile => return None,
+                _ => kind,
+            }
+        } else {
+            kind
+        };
+
+        if kind == FileKind::RegularFile {
+            None
+        } else {
+            Some(format!("{} is a {}, not a regular file", path.display(), kind.label()))
+        }
+    }
+
+    /// Whether this scan's base path is a slow, error-prone MTP/phone mount
+    /// (gvfs or a user-session FUSE mount), where concurrent reads tend to be
+    /// slower than sequential ones and more prone to transient I/O errors.
+    /// Callers that would otherwise parallelize per-file work should check
+    /// this first and fall back to a single worker.
+    pub fn is_mtp_path(&self) -> bool {
+        is_mtp_path(&self.base_path)
+    }
+
     pub fn create_move_script(&self, moves: &[(String, String, u32)]) -> Result<()> {
         let script_path = std::env::temp_dir().join("move_tiktok_files.sh");
         let mut script_content = String::new();
@@ -575,60 +1130,174 @@ impl FileManager {
         Ok(())
     }
 
-    /// Creates a manual organization guide for phone filesystems
-    pub fn create_phone_organization_guide(&self, detected_files: &[(String, u32)]) -> Result<()> {
+    /// Appends a "🔁 Duplicate Clips" section grouping visually identical
+    /// videos among `detected_files` (re-downloads, re-encodes, different
+    /// resolutions of the same clip) via `VideoDuplicateDetector`. Non-video
+    /// files and videos that can't be hashed (no ffmpeg, unreadable) are
+    /// simply excluded from every group, so this is a no-op on an
+    /// image-only detection set. Each cluster names one representative file
+    /// (the largest copy, as the one most likely to be the original-quality
+    /// upload) and lists the rest as redundant so the manual steps below can
+    /// say "keep X, delete Y/Z".
+    fn append_duplicate_clips_section(&self, guide_content: &mut String, detected_files: &[FileMetadata]) {
+        let detector = VideoDuplicateDetector::new();
+        let groups: Vec<Vec<FileMetadata>> = detector.find_duplicate_groups(detected_files)
+            .into_iter()
+            .filter(|group| group.len() > 1)
+            .collect();
+
+        if groups.is_empty() {
+            return;
+        }
+
+        guide_content.push_str("### 🔁 Duplicate Clips (same video saved more than once)\n");
+        guide_content.push_str("These look like the same clip saved multiple times (re-download, re-encode, or a different resolution). Keep one copy and delete the rest:\n\n");
+
+        for group in &groups {
+            let Some(representative) = group.iter().max_by_key(|m| m.size_bytes) else { continue };
+            let redundant: Vec<&str> = group.iter()
+                .filter(|m| m.filepath != representative.filepath)
+                .map(|m| m.filename.as_str())
+                .collect();
+
+            guide_content.push_str(&format!(
+                "- Keep `{}` ({}) — delete: {}\n",
+                representative.filename,
+                representative.size_human,
+                redundant.iter().map(|name| format!("`{}`", name)).collect::<Vec<_>>().join(", ")
+            ));
+        }
+        guide_content.push_str("\n");
+    }
+
+    /// Appends a "⚠️ Extension Mismatches" section listing every detected
+    /// file whose extension disagrees with the container signature found in
+    /// its header (see `MetadataManager::verify_extension`), with the
+    /// extension it should probably be renamed to.
+    fn append_extension_mismatch_section(&self, guide_content: &mut String, detected_files: &[FileMetadata]) {
+        let mismatched: Vec<&FileMetadata> = detected_files.iter()
+            .filter(|m| m.extension_mismatch.is_some())
+            .collect();
+
+        if mismatched.is_empty() {
+            return;
+        }
+
+        guide_content.push_str("### ⚠️ Extension Mismatches\n");
+        guide_content.push_str("These files' headers don't match their extension. Media renamed this way is common for TikTok exports; anything with no suggested extension below isn't recognized as media at all and was excluded from the counts above:\n\n");
+
+        for metadata in mismatched {
+            let mismatch = metadata.extension_mismatch.as_ref().unwrap();
+            if mismatch.suggested_extension.is_empty() {
+                guide_content.push_str(&format!(
+                    "- `{}` claims `.{}` but its header is {}\n",
+                    metadata.filename, mismatch.claimed_extension, mismatch.sniffed_container
+                ));
+            } else {
+                guide_content.push_str(&format!(
+                    "- `{}` claims `.{}` but its header is {} — consider renaming to `.{}`\n",
+                    metadata.filename, mismatch.claimed_extension, mismatch.sniffed_container, mismatch.suggested_extension
+                ));
+            }
+        }
+        guide_content.push_str("\n");
+    }
+
+    /// Same as `create_phone_organization_guide`, but additionally writes one
+    /// sibling file per format in `export_formats` (e.g.
+    /// `tiktok_phone_organization_guide.json`) holding the same
+    /// `detected_files` as machine-readable `AnalysisRecord`s, so results can
+    /// be piped into scripts/CI instead of hand-following the markdown steps.
+    /// The markdown guide is always written; extra formats are opt-in and additive.
+    pub fn create_phone_organization_guide_with_exports(&self, detected_files: &[FileMetadata], export_formats: &[ExportFormat], skipped_zeroed: usize, demoted_tiny_videos: usize) -> Result<()> {
+        self.create_phone_organization_guide(detected_files, skipped_zeroed, demoted_tiny_videos)?;
+
+        let guide_dir = std::env::temp_dir();
+        for format in export_formats {
+            let filename = match format {
+                ExportFormat::PrettyJson => "tiktok_phone_organization_guide.json",
+                ExportFormat::CompactJson => "tiktok_phone_organization_guide.compact.json",
+                ExportFormat::Ndjson => "tiktok_phone_organization_guide.ndjson",
+                ExportFormat::Csv => "tiktok_phone_organization_guide.csv",
+            };
+            detected_files.export(&guide_dir.join(filename), *format)?;
+        }
+
+        Ok(())
+    }
+
+    /// Creates a manual organization guide for phone filesystems.
+    /// `skipped_zeroed` and `demoted_tiny_videos` are reported in the
+    /// "Detection Summary" section so the user understands why the counts
+    /// above differ from a raw directory listing.
+    pub fn create_phone_organization_guide(&self, detected_files: &[FileMetadata], skipped_zeroed: usize, demoted_tiny_videos: usize) -> Result<()> {
         let guide_path = std::env::temp_dir().join("tiktok_phone_organization_guide.md");
         let mut guide_content = String::new();
-        
+
         guide_content.push_str("# TikTok Files Organization Guide for Phone\n\n");
         guide_content.push_str("Due to phone filesystem limitations, files could not be automatically moved.\n");
         guide_content.push_str("Please follow these steps to manually organize your TikTok files:\n\n");
-        
+
         guide_content.push_str("## Detected TikTok Files\n\n");
-        
+
         let mut confirmed_files = Vec::new();
         let mut likely_files = Vec::new();
         let mut possible_files = Vec::new();
-        
-        for (file_path, confidence) in detected_files {
-            let filename = std::path::Path::new(file_path).file_name()
-                .unwrap_or_default().to_string_lossy();
-            
-            match *confidence {
-                70.. => confirmed_files.push((filename.to_string(), confidence)),
-                40..=69 => likely_files.push((filename.to_string(), confidence)),
-                20..=39 => possible_files.push((filename.to_string(), confidence)),
+
+        for metadata in detected_files {
+            let filename = metadata.filename.clone();
+            let confidence = metadata.tiktok_analysis.confidence_score;
+            let tag_match = metadata.mp4_tag_match.as_ref()
+                .map(|m| format!("{}=\"{}\"", m.atom, m.value));
+
+            match confidence {
+                70.. => confirmed_files.push((filename, confidence, tag_match)),
+                40..=69 => likely_files.push((filename, confidence, tag_match)),
+                20..=39 => possible_files.push((filename, confidence, tag_match)),
                 _ => {} // Skip unlikely files
             }
         }
-        
+
+        /// Renders one detected-file line, e.g. `vid_123.mp4 (95% — encoder="TikTok")`
+        /// when a metadata atom justifies the rating, or the plain
+        /// `vid_123.mp4 (95% confidence)` form otherwise.
+        fn format_detected_file_line(filename: &str, confidence: u32, tag_match: &Option<String>) -> String {
+            match tag_match {
+                Some(tag_match) => format!("- `{}` ({}% — {})\n", filename, confidence, tag_match),
+                None => format!("- `{}` ({}% confidence)\n", filename, confidence),
+            }
+        }
+
         if !confirmed_files.is_empty() {
             guide_content.push_str("### 🔴 Confirmed TikTok Files (70%+ confidence)\n");
             guide_content.push_str("These files are almost certainly from TikTok:\n\n");
-            for (filename, confidence) in confirmed_files {
-                guide_content.push_str(&format!("- `{}` ({}% confidence)\n", filename, confidence));
+            for (filename, confidence, tag_match) in confirmed_files {
+                guide_content.push_str(&format_detected_file_line(&filename, confidence, &tag_match));
             }
             guide_content.push_str("\n");
         }
-        
+
         if !likely_files.is_empty() {
             guide_content.push_str("### 🟡 Likely TikTok Files (40-69% confidence)\n");
             guide_content.push_str("These files are probably from TikTok:\n\n");
-            for (filename, confidence) in likely_files {
-                guide_content.push_str(&format!("- `{}` ({}% confidence)\n", filename, confidence));
+            for (filename, confidence, tag_match) in likely_files {
+                guide_content.push_str(&format_detected_file_line(&filename, confidence, &tag_match));
             }
             guide_content.push_str("\n");
         }
-        
+
         if !possible_files.is_empty() {
             guide_content.push_str("### 🔵 Possible TikTok Files (20-39% confidence)\n");
             guide_content.push_str("These files might be from TikTok:\n\n");
-            for (filename, confidence) in possible_files {
-                guide_content.push_str(&format!("- `{}` ({}% confidence)\n", filename, confidence));
+            for (filename, confidence, tag_match) in possible_files {
+                guide_content.push_str(&format_detected_file_line(&filename, confidence, &tag_match));
             }
             guide_content.push_str("\n");
         }
-        
+
+        self.append_duplicate_clips_section(&mut guide_content, detected_files);
+        self.append_extension_mismatch_section(&mut guide_content, detected_files);
+
         guide_content.push_str("## Manual Organization Steps\n\n");
         guide_content.push_str("1. **On your phone**, navigate to your file manager\n");
         guide_content.push_str("2. **Go to the scan folder**: ");
@@ -648,6 +1317,12 @@ impl FileManager {
         guide_content.push_str("## Detection Summary\n\n");
         guide_content.push_str(&format!("- **Scan folder**: `{}`\n", self.base_path.display()));
         guide_content.push_str(&format!("- **Target folder**: `{}`\n", self.tiktok_folder.display()));
+        if skipped_zeroed > 0 {
+            guide_content.push_str(&format!("- **Skipped (zero-length or all-zero content)**: {}\n", skipped_zeroed));
+        }
+        if demoted_tiny_videos > 0 {
+            guide_content.push_str(&format!("- **Demoted from possible (under minimum video size)**: {}\n", demoted_tiny_videos));
+        }
         guide_content.push_str(&format!("- **Generated**: {}\n", chrono::Utc::now().to_rfc3339()));
         
         fs::write(&guide_path, guide_content)
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use crate::tiktok_detection::file_util::atomic_write::write_atomic;
+
+/// One directory entry as recorded in a `DirSchema`: just enough to rebuild
+/// a `MediaFileEntry` for a file, or to know to recurse for a directory,
+/// without a second `stat`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: String,
+}
+
+/// A directory's listing as of `dir_modified` (that directory's own mtime,
+/// not its children's). As long as `dir_modified` is unchanged, no entry has
+/// been added, removed, or renamed directly under it, so `entries` can be
+/// trusted without re-reading the directory or re-stating its children.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DirSchema {
+    dir_modified: String,
+    entries: Vec<SchemaEntry>,
+}
+
+/// Caches directory listings keyed by path, invalidated per-directory by
+/// that directory's own mtime. Mirrors czkawka's cached-filesystem-schema
+/// approach: a repeat scan of an unchanged MTP-mounted phone tree can skip
+/// the (slow, one-round-trip-per-call) `read_dir`/`stat` pass entirely and
+/// reuse the previous run's listing, while a directory that did change only
+/// pays for re-reading that one directory rather than the whole tree.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FsSchemaCache {
+    schema: HashMap<String, DirSchema>,
+}
+
+impl FsSchemaCache {
+    fn new() -> Self {
+        FsSchemaCache { schema: HashMap::new() }
+    }
+
+    pub fn load_from_file(json_path: &Path) -> Self {
+        if !json_path.exists() {
+            return Self::new();
+        }
+
+        fs::read_to_string(json_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(Self::new)
+    }
+
+    pub fn save_to_file(&self, json_path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .context("Failed to serialize filesystem schema cache")?;
+        // Same temp-file-and-rename dance as `NotTikTokCache::save_to_file`,
+        // so a killed-mid-write process can't leave a truncated schema
+        // cache that a later load would silently reset to empty.
+        write_atomic(json_path, &content)
+    }
+}
+
+/// Thread-safe wrapper around `FsSchemaCache` so the parallel directory walk
+/// in `TikTokScanner::get_media_files` can share one cache across the rayon
+/// worker pool instead of each top-level branch keeping its own.
+pub struct SharedFsSchemaCache {
+    cache_file: std::path::PathBuf,
+    inner: Mutex<FsSchemaCache>,
+}
+
+impl SharedFsSchemaCache {
+    pub fn load(cache_file: std::path::PathBuf) -> Self {
+        let inner = Mutex::new(FsSchemaCache::load_from_file(&cache_file));
+        SharedFsSchemaCache { cache_file, inner }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.inner.lock().unwrap().save_to_file(&self.cache_file)
+    }
+
+    /// Returns `dir`'s entries, reusing the cached listing if `dir`'s own
+    /// mtime still matches what was recorded last time, and re-reading (then
+    /// caching) it otherwise.
+    pub fn list_dir(&self, dir: &Path) -> Result<Vec<SchemaEntry>> {
+        let dir_modified = Self::mtime_string(dir)
+            .with_context(|| format!("Failed to stat directory {}", dir.display()))?;
+        let key = dir.to_string_lossy().to_string();
+
+        {
+            let cache = self.inner.lock().unwrap();
+            if let Some(cached) = cache.schema.get(&key) {
+                if cached.dir_modified == dir_modified {
+                    return Ok(cached.entries.clone());
+                }
+            }
+        }
+
+        let entries = Self::scan_dir(dir)?;
+
+        let mut cache = self.inner.lock().unwrap();
+        cache.schema.insert(key, DirSchema { dir_modified, entries: entries.clone() });
+
+        Ok(entries)
+    }
+
+    fn scan_dir(dir: &Path) -> Result<Vec<SchemaEntry>> {
+        let read_dir = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read directory {}", dir.display()))?;
+
+        let mut entries = Vec::new();
+        for entry in read_dir {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(_) => continue,
+            };
+
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+
+            let modified = match Self::system_time_to_rfc3339(metadata.modified()) {
+                Some(modified) => modified,
+                None => continue,
+            };
+
+            entries.push(SchemaEntry {
+                name: entry.file_name().to_string_lossy().to_string(),
+                is_dir: metadata.is_dir(),
+                size: metadata.len(),
+                modified,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    fn mtime_string(path: &Path) -> Result<String> {
+        let metadata = fs::metadata(path)?;
+        Self::system_time_to_rfc3339(metadata.modified())
+            .context("Directory has no modification time")
+    }
+
+    fn system_time_to_rfc3339(modified: std::io::Result<std::time::SystemTime>) -> Option<String> {
+        let modified = modified.ok()?;
+        let since_epoch = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+        Some(chrono::DateTime::<chrono::Utc>::from(std::time::UNIX_EPOCH + since_epoch).to_rfc3339())
+    }
+}
@@ -0,0 +1,148 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{bail, Context, Result};
+
+/// Maximum symlink hops resolved before a chain is treated as an infinite
+/// loop, mirroring czkawka's ~20-hop cap rather than recursing unbounded.
+const MAX_SYMLINK_HOPS: u32 = 20;
+
+/// Strips anything from `filename` that could escape the destination
+/// directory or corrupt it when joined onto a path: embedded separators,
+/// NUL bytes, and a leading run of dots (which would otherwise produce a
+/// `..`/`.` path-traversal component, or silently hide the file as
+/// dotfiles do on Unix). Names that sanitize to nothing fall back to a
+/// fixed placeholder instead of producing an empty path.
+pub fn sanitize_filename(filename: &OsStr) -> String {
+    let raw = filename.to_string_lossy();
+    let replaced: String = raw.chars().map(|c| if matches!(c, '/' | '\\' | '\0') { '_' } else { c }).collect();
+    let sanitized = replaced.trim_start_matches('.');
+
+    if sanitized.is_empty() {
+        "unnamed_file".to_string()
+    } else {
+        sanitized.to_string()
+    }
+}
+
+/// Joins `filename` under `target_dir` after sanitizing it, then verifies
+/// the canonicalized result still resolves inside `target_dir` - defense
+/// in depth against a sanitization gap or a symlink planted inside
+/// `target_dir` that points back out of it.
+pub fn safe_join(target_dir: &Path, filename: &OsStr) -> Result<PathBuf> {
+    let sanitized = sanitize_filename(filename);
+    let candidate = target_dir.join(&sanitized);
+
+    let canonical_target_dir = target_dir
+        .canonicalize()
+        .with_context(|| format!("Failed to canonicalize target directory {}", target_dir.display()))?;
+
+    // The candidate file itself usually doesn't exist yet, so canonicalize
+    // its (existing) parent and re-attach the sanitized filename, rather
+    // than canonicalizing the whole candidate path.
+    let candidate_parent = candidate.parent().context("Destination path has no parent")?;
+    let canonical_parent = candidate_parent
+        .canonicalize()
+        .with_context(|| format!("Failed to canonicalize destination parent {}", candidate_parent.display()))?;
+
+    if !canonical_parent.starts_with(&canonical_target_dir) {
+        bail!(
+            "Refusing to write outside target directory: {} resolves to {}",
+            candidate.display(),
+            canonical_parent.display()
+        );
+    }
+
+    Ok(canonical_parent.join(&sanitized))
+}
+
+/// Resolves `path` through up to `MAX_SYMLINK_HOPS` symlink hops, returning
+/// an error if the chain is still a symlink afterward (treated as an
+/// infinite loop) or any hop points at a nonexistent target. Returns the
+/// final non-symlink path on success.
+pub fn resolve_symlink_bounded(path: &Path) -> Result<PathBuf> {
+    let mut current = path.to_path_buf();
+
+    for _ in 0..MAX_SYMLINK_HOPS {
+        let metadata = fs::symlink_metadata(&current)
+            .with_context(|| format!("NonExistentFile: {} does not exist", current.display()))?;
+
+        if !metadata.file_type().is_symlink() {
+            return Ok(current);
+        }
+
+        let target = fs::read_link(&current).with_context(|| format!("Failed to read symlink {}", current.display()))?;
+
+        // `read_link` returns the literal target text. A relative target
+        // (the common case, e.g. `link.jpg -> photo.jpg` in the same
+        // directory) is meant to resolve against the symlink's own parent
+        // directory, not whatever the process's CWD happens to be - so
+        // re-anchor it here before the next hop looks it up.
+        current = if target.is_relative() {
+            match current.parent() {
+                Some(parent) => parent.join(target),
+                None => target,
+            }
+        } else {
+            target
+        };
+    }
+
+    bail!("InfiniteRecursion: symlink chain at {} exceeded {} hops", path.display(), MAX_SYMLINK_HOPS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn resolves_relative_symlink_in_same_directory() {
+        let dir = std::env::temp_dir().join(format!("path_safety_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let target = dir.join("photo.jpg");
+        fs::write(&target, b"data").unwrap();
+
+        let link = dir.join("link.jpg");
+        let _ = fs::remove_file(&link);
+        symlink("photo.jpg", &link).unwrap();
+
+        let resolved = resolve_symlink_bounded(&link).expect("relative symlink should resolve");
+        assert_eq!(resolved, target);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn resolves_absolute_symlink() {
+        let dir = std::env::temp_dir().join(format!("path_safety_test_abs_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let target = dir.join("photo.jpg");
+        fs::write(&target, b"data").unwrap();
+
+        let link = dir.join("link.jpg");
+        let _ = fs::remove_file(&link);
+        symlink(&target, &link).unwrap();
+
+        let resolved = resolve_symlink_bounded(&link).expect("absolute symlink should resolve");
+        assert_eq!(resolved, target);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn errors_on_missing_target() {
+        let dir = std::env::temp_dir().join(format!("path_safety_test_missing_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let link = dir.join("link.jpg");
+        let _ = fs::remove_file(&link);
+        symlink("does_not_exist.jpg", &link).unwrap();
+
+        assert!(resolve_symlink_bounded(&link).is_err());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
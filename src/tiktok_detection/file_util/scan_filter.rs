@@ -0,0 +1,189 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+/// A single `*`-wildcard pattern, compiled once into literal segments split
+/// on `*` so matching a path is an ordered substring scan instead of
+/// re-parsing the pattern string on every file checked during a walk.
+#[derive(Debug, Clone)]
+struct WildcardPattern {
+    segments: Vec<String>,
+    anchored_start: bool,
+    anchored_end: bool,
+}
+
+impl WildcardPattern {
+    fn compile(pattern: &str) -> Self {
+        let pattern = pattern.to_lowercase();
+        let anchored_start = !pattern.starts_with('*');
+        let anchored_end = !pattern.ends_with('*');
+        let segments: Vec<String> = pattern.split('*').map(str::to_string).filter(|s| !s.is_empty()).collect();
+
+        WildcardPattern { segments, anchored_start, anchored_end }
+    }
+
+    fn matches(&self, haystack: &str) -> bool {
+        if self.segments.is_empty() {
+            // The pattern was just "*" (match everything) or empty.
+            return true;
+        }
+
+        let mut cursor = 0;
+        for (i, segment) in self.segments.iter().enumerate() {
+            match haystack[cursor..].find(segment.as_str()) {
+                Some(relative_pos) => {
+                    if i == 0 && self.anchored_start && relative_pos != 0 {
+                        return false;
+                    }
+                    cursor += relative_pos + segment.len();
+                }
+                None => return false,
+            }
+        }
+
+        if self.anchored_end {
+            let last_segment = self.segments.last().unwrap();
+            if !haystack.ends_with(last_segment.as_str()) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Extensions treated as scannable media, and also the expansion of the
+/// `VIDEO`/`IMAGE` preset keywords accepted anywhere an extension list is
+/// (so a user can pass `VIDEO` instead of spelling out every container by
+/// hand, modeled on czkawka's built-in `Extensions` presets). Kept as the
+/// single source of truth here — `scanner.rs`'s directory walk and
+/// video/image branch both import these rather than keeping their own copy,
+/// so a preset can never admit or drop a format the scanner itself doesn't
+/// recognize.
+pub const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "avi", "mkv", "flv", "webm"];
+pub const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "gif", "bmp"];
+
+/// Expands `VIDEO`/`IMAGE` preset keywords in `entries` (case-insensitive)
+/// to their concrete extensions, and normalizes the rest: strips a leading
+/// dot and lowercases. Empty tokens are dropped with a warning; tokens with
+/// an embedded dot (e.g. a stray `tar.gz`) are kept as-is but warned about,
+/// since they'll never match a real single-extension file.
+fn expand_and_normalize(entries: &[String]) -> Vec<String> {
+    let mut result = Vec::new();
+    for entry in entries {
+        match entry.to_uppercase().as_str() {
+            "VIDEO" => {
+                result.extend(VIDEO_EXTENSIONS.iter().map(|ext| ext.to_string()));
+                continue;
+            }
+            "IMAGE" => {
+                result.extend(IMAGE_EXTENSIONS.iter().map(|ext| ext.to_string()));
+                continue;
+            }
+            _ => {}
+        }
+
+        let trimmed = entry.trim_start_matches('.');
+        if trimmed.is_empty() {
+            eprintln!("Warning: ignoring empty extension entry {:?}", entry);
+            continue;
+        }
+        if trimmed.contains('.') {
+            eprintln!("Warning: extension entry {:?} contains an embedded dot; it will never match, since a file's extension is only what follows its last dot", entry);
+        }
+        result.push(trimmed.to_lowercase());
+    }
+
+    result
+}
+
+/// Case-normalized extension allow/deny list, following czkawka's
+/// `Extensions` model. An empty allow-list means "no restriction"; the
+/// exclude list always takes precedence over it. Entries may be bare
+/// extensions or the `VIDEO`/`IMAGE` presets (see `expand_and_normalize`).
+#[derive(Debug, Clone, Default)]
+pub struct Extensions {
+    allowed: Option<HashSet<String>>,
+    excluded: HashSet<String>,
+}
+
+impl Extensions {
+    pub fn new(allowed: &[String], excluded: &[String]) -> Self {
+        let allowed = expand_and_normalize(allowed);
+        let allowed = if allowed.is_empty() {
+            None
+        } else {
+            Some(allowed.into_iter().collect())
+        };
+        let excluded = expand_and_normalize(excluded).into_iter().collect();
+
+        Extensions { allowed, excluded }
+    }
+
+    /// Whether `path`'s extension passes the allow/exclude lists. A path
+    /// with no extension never passes.
+    pub fn is_allowed(&self, path: &Path) -> bool {
+        let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+            return false;
+        };
+        let ext = ext.to_lowercase();
+
+        if self.excluded.contains(&ext) {
+            return false;
+        }
+
+        match &self.allowed {
+            Some(allowed) => allowed.contains(&ext),
+            None => true,
+        }
+    }
+}
+
+/// A compiled set of directory-name wildcard patterns (e.g. `Android*`,
+/// `*WhatsApp*`), matched against every path component so a whole subtree
+/// can be skipped during traversal rather than filtered file-by-file.
+#[derive(Debug, Clone, Default)]
+pub struct ExcludedDirectories {
+    patterns: Vec<WildcardPattern>,
+}
+
+impl ExcludedDirectories {
+    pub fn new(patterns: &[String]) -> Self {
+        ExcludedDirectories { patterns: patterns.iter().map(|p| WildcardPattern::compile(p)).collect() }
+    }
+
+    /// True if any component of `path` matches one of the compiled patterns.
+    pub fn excludes(&self, path: &Path) -> bool {
+        if self.patterns.is_empty() {
+            return false;
+        }
+
+        path.components().any(|component| {
+            let component_str = component.as_os_str().to_string_lossy().to_lowercase();
+            self.patterns.iter().any(|pattern| pattern.matches(&component_str))
+        })
+    }
+}
+
+/// Combines an `Extensions` allow/deny list with `ExcludedDirectories`,
+/// compiled once up front and reused across an entire folder walk instead
+/// of being rebuilt (or re-parsed from pattern strings) per file.
+#[derive(Debug, Clone, Default)]
+pub struct ScanFilter {
+    pub extensions: Extensions,
+    pub excluded_dirs: ExcludedDirectories,
+}
+
+impl ScanFilter {
+    pub fn new(allowed_extensions: &[String], excluded_extensions: &[String], excluded_dirs: &[String]) -> Self {
+        ScanFilter {
+            extensions: Extensions::new(allowed_extensions, excluded_extensions),
+            excluded_dirs: ExcludedDirectories::new(excluded_dirs),
+        }
+    }
+
+    /// Whether `path` should be analyzed at all: its extension passes
+    /// `extensions` and no ancestor component matches `excluded_dirs`.
+    pub fn allows(&self, path: &Path) -> bool {
+        self.extensions.is_allowed(path) && !self.excluded_dirs.excludes(path)
+    }
+}
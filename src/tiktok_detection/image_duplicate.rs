@@ -0,0 +1,192 @@
+use std::path::Path;
+use std::collections::HashMap;
+use anyhow::{Context, Result};
+use image_hasher::{HasherConfig, HashAlg, FilterType, ImageHash};
+use crate::tiktok_detection::metadata_read::metadata_manager::FileMetadata;
+use crate::tiktok_detection::perceptual_hash::hamming;
+use crate::tiktok_detection::union_find::UnionFind;
+
+/// Computes perceptual image hashes via the `image_hasher` crate, for
+/// grouping visually-identical re-saves (different byte content, same
+/// picture) the way `md5_hash` alone can't. Distinct from `PerceptualHasher`
+/// (hand-rolled dHash used for watermark-region matching against a fixed
+/// `TikTokHashDatabase`) - this one targets general image-duplicate
+/// clustering across a whole scan, so it exposes the full `HashAlg`/
+/// `FilterType` configuration space `image_hasher` supports rather than a
+/// single fixed recipe.
+pub struct ImageHasher {
+    hasher: image_hasher::Hasher,
+}
+
+impl ImageHasher {
+    /// Builds a hasher using `alg` for bit extraction and `filter` for the
+    /// resize step that precedes it.
+    pub fn new(alg: HashAlg, filter: FilterType) -> Self {
+        let hasher = HasherConfig::new()
+            .hash_alg(alg)
+            .resize_filter(filter)
+            .to_hasher();
+        ImageHasher { hasher }
+    }
+
+    /// Computes a base64-encoded perceptual hash for the image at `filepath`.
+    pub fn hash_file(&self, filepath: &Path) -> Result<String> {
+        let img = image::open(filepath).with_context(|| format!("Failed to open image: {:?}", filepath))?;
+        Ok(self.hash_image(&img))
+    }
+
+    /// Computes a base64-encoded perceptual hash directly from an
+    /// already-decoded image, for callers (like
+    /// `MetadataManager::analyze_file_with_options`) that decode the image
+    /// once and share it with other per-image passes.
+    pub fn hash_image(&self, img: &image::DynamicImage) -> String {
+        self.hasher.hash_image(img).to_base64()
+    }
+}
+
+impl Default for ImageHasher {
+    /// Mean hash over a Lanczos3 resize - a reasonable general-purpose
+    /// default that tolerates re-encoding and minor resizing.
+    fn default() -> Self {
+        Self::new(HashAlg::Mean, FilterType::Lanczos3)
+    }
+}
+
+/// Decodes a base64 hash (as produced by `ImageHasher::hash_file`) back into
+/// a `u64` for Hamming-distance comparisons. Returns `None` for malformed
+/// input or hashes wider than 64 bits - which won't happen for any of the
+/// four `HashAlg` variants `ImageHasher::new` accepts, since the hash size
+/// itself isn't configurable here and stays at `image_hasher`'s 8x8 (64-bit)
+/// default for all of them.
+fn decode_hash(encoded: &str) -> Option<u64> {
+    let hash = ImageHash::from_base64(encoded).ok()?;
+    let bytes = hash.as_bytes();
+    if bytes.len() > 8 {
+        return None;
+    }
+    let mut buf = [0u8; 8];
+    buf[..bytes.len()].copy_from_slice(bytes);
+    Some(u64::from_le_bytes(buf))
+}
+
+/// Similarity tiers mirroring czkawka's "Minimal/Small/Medium/High" duplicate
+/// presets, mapped onto a Hamming-distance tolerance out of 64 bits. Higher
+/// tiers accept more differing bits, so they catch more aggressive
+/// re-encodes/crops at the cost of more false positives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimilarityPreset {
+    Minimal,
+    Small,
+    Medium,
+    High,
+}
+
+impl SimilarityPreset {
+    pub fn max_distance(&self) -> u32 {
+        match self {
+            SimilarityPreset::Minimal => 1,
+            SimilarityPreset::Small => 5,
+            SimilarityPreset::Medium => 10,
+            SimilarityPreset::High => 15,
+        }
+    }
+}
+
+/// A node in a BK-tree of image hashes, indexed by position in the caller's
+/// result slice (mirrors `video_duplicate::VideoBkTree` - duplicate groups
+/// need every match within tolerance, not just the closest one).
+struct ImageBkNode {
+    hash: u64,
+    index: usize,
+    children: Vec<(u32, ImageBkNode)>,
+}
+
+struct ImageBkTree {
+    root: Option<ImageBkNode>,
+}
+
+impl ImageBkTree {
+    fn new() -> Self {
+        ImageBkTree { root: None }
+    }
+
+    fn insert(&mut self, hash: u64, index: usize) {
+        match &mut self.root {
+            None => self.root = Some(ImageBkNode { hash, index, children: Vec::new() }),
+            Some(root) => Self::insert_node(root, hash, index),
+        }
+    }
+
+    fn insert_node(node: &mut ImageBkNode, hash: u64, index: usize) {
+        let distance = hamming(node.hash, hash);
+        if let Some((_, child)) = node.children.iter_mut().find(|(d, _)| *d == distance) {
+            Self::insert_node(child, hash, index);
+        } else {
+            node.children.push((distance, ImageBkNode { hash, index, children: Vec::new() }));
+        }
+    }
+
+    /// Returns the index of every entry within `tolerance` bits of `hash`.
+    fn find_all_within(&self, hash: u64, tolerance: u32) -> Vec<usize> {
+        let mut matches = Vec::new();
+        if let Some(root) = &self.root {
+            Self::search_node(root, hash, tolerance, &mut matches);
+        }
+        matches
+    }
+
+    fn search_node(node: &ImageBkNode, hash: u64, tolerance: u32, matches: &mut Vec<usize>) {
+        let distance = hamming(node.hash, hash);
+        if distance <= tolerance {
+            matches.push(node.index);
+        }
+
+        let low = distance.saturating_sub(tolerance);
+        let high = distance + tolerance;
+        for (edge, child) in &node.children {
+            if *edge >= low && *edge <= high {
+                Self::search_node(child, hash, tolerance, matches);
+            }
+        }
+    }
+}
+
+/// Groups `results` whose `perceptual_hash` falls within `preset`'s
+/// tolerance of each other. Files without a perceptual hash (videos, images
+/// the hasher couldn't decode) are excluded from every group.
+pub fn group_similar(results: &[FileMetadata], preset: SimilarityPreset) -> Vec<Vec<FileMetadata>> {
+    group_similar_with_distance(results, preset.max_distance())
+}
+
+/// Same as `group_similar`, but with an explicit Hamming-distance tolerance
+/// (0-64 bits) instead of a named preset.
+pub fn group_similar_with_distance(results: &[FileMetadata], max_distance: u32) -> Vec<Vec<FileMetadata>> {
+    let mut hashes: Vec<(usize, u64)> = Vec::new();
+    for (index, metadata) in results.iter().enumerate() {
+        if let Some(encoded) = &metadata.perceptual_hash {
+            if let Some(hash) = decode_hash(encoded) {
+                hashes.push((index, hash));
+            }
+        }
+    }
+
+    let mut tree = ImageBkTree::new();
+    for (index, hash) in &hashes {
+        tree.insert(*hash, *index);
+    }
+
+    let mut union_find = UnionFind::new(results.len());
+    for (index, hash) in &hashes {
+        for matched_index in tree.find_all_within(*hash, max_distance) {
+            union_find.union(*index, matched_index);
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<FileMetadata>> = HashMap::new();
+    for (index, _) in &hashes {
+        let root = union_find.find(*index);
+        groups.entry(root).or_default().push(results[*index].clone());
+    }
+
+    groups.into_values().filter(|group| group.len() > 1).collect()
+}
@@ -1,10 +1,15 @@
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
-use regex::Regex;
 use image::GenericImageView;
+use rayon::prelude::*;
+use crate::tiktok_detection::video_probe::Mp4TagMatch;
+use crate::tiktok_detection::metadata_read::video_container;
+use crate::tiktok_detection::metadata_read::mime_sniff;
+use crate::tiktok_detection::metadata_read::steg_detect;
+use crate::tiktok_detection::metadata_read::rule_config::RuleSet;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TikTokEvidence {
@@ -15,6 +20,30 @@ pub struct TikTokEvidence {
     pub verdict: String,
 }
 
+/// A separate analysis track from `TikTokEvidence`: whether a file shows
+/// signs of being AI-generated (TikTok watermarks/labels AIGC uploads
+/// distinctly from ordinary ones), with its own evidence and verdict so
+/// callers can filter or quarantine AI-generated clips independently of
+/// plain TikTok-origin detection.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AigcAnalysis {
+    pub is_ai_generated: bool,
+    pub evidence_found: Vec<String>,
+    pub verdict: String,
+}
+
+/// Recorded when a file's extension disagrees with the container signature
+/// found in its first bytes (e.g. a `.txt` that is actually an MP4, or a
+/// `.mp4` that is really a JPEG thumbnail). `suggested_extension` is empty
+/// when the header didn't match any known signature at all, since then
+/// there's no container to suggest renaming to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtensionMismatch {
+    pub claimed_extension: String,
+    pub sniffed_container: String,
+    pub suggested_extension: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileMetadata {
     pub filename: String,
@@ -22,11 +51,29 @@ pub struct FileMetadata {
     pub size_bytes: u64,
     pub size_human: String,
     pub md5_hash: Option<String>,
+    /// Base64-encoded perceptual hash (see `image_duplicate::ImageHasher`),
+    /// for matching visually-identical re-saves/re-encodes that differ in
+    /// byte content from `md5_hash`. `None` for video files (which get their
+    /// own frame-sampled hash via `VideoDuplicateDetector`) or images the
+    /// hasher couldn't decode.
+    pub perceptual_hash: Option<String>,
     pub dimensions: Option<(u32, u32)>,
     pub aspect_ratio: Option<f64>,
     pub file_format: Option<String>,
     pub strings_found: Vec<String>,
+    pub extension_mismatch: Option<ExtensionMismatch>,
+    /// Set by `TikTokVideoDetector::analyze_video` when an MP4/MOV metadata
+    /// atom matched a TikTok fingerprint; `None` for images or videos with no
+    /// matching tag.
+    pub mp4_tag_match: Option<Mp4TagMatch>,
+    /// Set when `steg_detect::analyze` flags this image's LSB plane as
+    /// suspiciously close to random payload data rather than natural pixel
+    /// noise. Always `false` for video files. See `steg_detect` for what
+    /// "suspicious" means here - this is a heuristic signal, not a proof of
+    /// a hidden payload.
+    pub steg_suspected: bool,
     pub tiktok_analysis: TikTokEvidence,
+    pub aigc_analysis: AigcAnalysis,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,41 +87,45 @@ pub struct ImageIndicators {
 }
 
 pub struct MetadataManager {
-    tiktok_dimensions: Vec<(u32, u32)>,
-    tiktok_video_id_regex: Regex,
-    string_indicators: Vec<String>,
+    rules: RuleSet,
+    image_hasher: crate::tiktok_detection::image_duplicate::ImageHasher,
 }
 
 impl MetadataManager {
+    /// Uses `RuleSet::built_in` - the same dimensions/string indicators/
+    /// video-ID pattern/camera-exclusion keywords this used to hardcode
+    /// directly, now loaded as the default ruleset instead.
     pub fn new() -> Result<Self> {
-        let tiktok_dimensions = vec![
-            (576, 1024), (576, 1246), (576, 1280),
-            (1080, 1920), (1080, 1800), (1080, 2340), (1080, 2400),
-            (828, 1792), (750, 1334), (1125, 2436), (1242, 2688),
-            (1284, 2778), (1170, 2532),
-        ];
-
-        let tiktok_video_id_regex = Regex::new(r"vid:v\d+g[fl]0000[a-f0-9]+")
-            .context("Failed to compile TikTok video ID regex")?;
-
-        let string_indicators = vec![
-            "tiktok".to_string(),
-            "douyin".to_string(), 
-            "bytedance".to_string(),
-            "musically".to_string(),
-            "musical.ly".to_string(),
-            "aigc_label_type".to_string(),
-            "vid_md5".to_string(),
-        ];
+        Self::new_with_ruleset(RuleSet::built_in())
+    }
 
+    /// Same as `new`, but evaluates `rules` instead of the built-in ruleset,
+    /// so callers can tune detection weights or add new ByteDance markers
+    /// without recompiling.
+    pub fn new_with_ruleset(rules: RuleSet) -> Result<Self> {
         Ok(MetadataManager {
-            tiktok_dimensions,
-            tiktok_video_id_regex,
-            string_indicators,
+            rules,
+            image_hasher: crate::tiktok_detection::image_duplicate::ImageHasher::default(),
         })
     }
 
+    /// Same as `new_with_ruleset`, but loads the ruleset from a TOML or
+    /// JSON file on disk (see `RuleSet::load`).
+    pub fn new_with_ruleset_file(ruleset_path: &Path) -> Result<Self> {
+        Self::new_with_ruleset(RuleSet::load(ruleset_path)?)
+    }
+
     pub fn analyze_file(&self, filepath: &Path) -> Result<FileMetadata> {
+        self.analyze_file_with_options(filepath, true)
+    }
+
+    /// Same as `analyze_file`, but lets the MD5 digest be skipped via
+    /// `compute_md5` for a faster "dimensions + strings only" pass (see
+    /// `analyze_folder_with_options`). The file is read into memory once
+    /// here and the same buffer is shared across the digest, the string
+    /// scan, and - for MP4/MOV - container box parsing, instead of each
+    /// doing its own `fs::read`.
+    pub fn analyze_file_with_options(&self, filepath: &Path, compute_md5: bool) -> Result<FileMetadata> {
         let filename = filepath.file_name()
             .unwrap_or_default()
             .to_string_lossy()
@@ -86,20 +137,87 @@ impl MetadataManager {
         let size_bytes = metadata.len();
         let size_human = format_bytes(size_bytes);
 
-        // Calculate MD5 hash
-        let md5_hash = self.calculate_md5(filepath).ok();
+        let file_bytes = fs::read(filepath).ok();
+
+        // Calculate MD5 hash, unless the caller only wants the cheaper
+        // dimensions-and-strings pass.
+        let md5_hash = if compute_md5 {
+            file_bytes.as_deref().map(Self::calculate_md5)
+        } else {
+            None
+        };
+
+        // Decoded once and shared between the perceptual hash and the
+        // steganalysis pass below, instead of each decoding the image
+        // itself. `None` for video files and for images the `image` crate
+        // can't decode.
+        let decoded_image = (!Self::is_video_extension(filepath))
+            .then(|| image::open(filepath).ok())
+            .flatten();
+
+        // Perceptual hash for near-duplicate visual matching (re-saves and
+        // re-encodes that differ in byte content, so `md5_hash` misses
+        // them). Skipped for video files, which get their own frame-sampled
+        // hash via `VideoDuplicateDetector` instead.
+        let perceptual_hash = decoded_image.as_ref().map(|img| self.image_hasher.hash_image(img));
 
         // Try to get image dimensions
-        let (dimensions, file_format) = self.get_image_info(filepath);
+        let (dimensions, file_format) = self.get_image_info(filepath, decoded_image.as_ref());
+
+        // The image crates above only understand still images, so MP4/MOV
+        // fall back to parsing the container's own ISO-BMFF box tree for
+        // track dimensions and udta tag strings (a real `.avi` isn't
+        // box-based and simply yields `None` here). Reuses `file_bytes`
+        // rather than reading the file a second time.
+        let video_container = file_bytes.as_deref()
+            .filter(|_| Self::is_video_extension(filepath))
+            .and_then(video_container::probe_container);
+
+        let dimensions = dimensions.or_else(|| {
+            video_container.as_ref().and_then(|info| Some((info.width?, info.height?)))
+        });
 
         // Calculate aspect ratio
         let aspect_ratio = dimensions.map(|(w, h)| w as f64 / h as f64);
 
         // Search for strings in file
-        let strings_found = self.search_strings_in_file(filepath);
+        let mut strings_found = file_bytes.as_deref()
+            .map(|data| self.search_strings_in_file(data))
+            .unwrap_or_default();
+        if let Some(info) = &video_container {
+            // Same indicator filter `search_strings_in_file` applies, so an
+            // unrelated udta tag string (e.g. an encoder/camera string) can't
+            // slip into `strings_found` and trip the camera-photo exclusion
+            // below on an otherwise-genuine TikTok export. Also skips
+            // anything `search_strings_in_file` already found (the udta atom
+            // is often within the first 1MB it already scanned), so a
+            // "fast-start" MP4 doesn't get the same tag string counted twice.
+            let new_udta_strings: Vec<String> = info.udta_strings.iter()
+                .filter(|s| !strings_found.contains(s))
+                .filter(|s| {
+                    let lower = s.to_lowercase();
+                    self.rules.all_string_patterns.iter().any(|indicator| lower.contains(indicator.as_str()))
+                })
+                .cloned()
+                .collect();
+            strings_found.extend(new_udta_strings);
+        }
+
+        // Flag a claimed extension that disagrees with the magic bytes actually
+        // found in the file (a renamed TikTok export, or a non-media file
+        // masquerading as one).
+        let extension_mismatch = Self::verify_extension(filepath, &file_format);
+
+        // Optional LSB steganalysis pass, flagging images whose
+        // least-significant-bit plane looks more like hidden payload data
+        // than natural pixel noise. Reuses the decode above; skipped for
+        // video files.
+        let steg_suspected = decoded_image.as_ref()
+            .map(|img| steg_detect::analyze(img).suspected)
+            .unwrap_or(false);
 
         // Analyze for TikTok evidence
-        let tiktok_analysis = self.analyze_tiktok_evidence(&filename, &dimensions, &aspect_ratio, &strings_found, &file_format);
+        let tiktok_analysis = self.analyze_tiktok_evidence(&filename, &dimensions, &aspect_ratio, &strings_found, &extension_mismatch, &video_container, steg_suspected);
 
         Ok(FileMetadata {
             filename,
@@ -107,31 +225,107 @@ impl MetadataManager {
             size_bytes,
             size_human,
             md5_hash,
+            perceptual_hash,
             dimensions,
             aspect_ratio,
             file_format,
             strings_found,
+            extension_mismatch,
+            mp4_tag_match: None,
+            steg_suspected,
             tiktok_analysis,
+            aigc_analysis: AigcAnalysis::default(),
         })
     }
 
-    fn calculate_md5(&self, filepath: &Path) -> Result<String> {
-        let data = fs::read(filepath)?;
-        let digest = md5::compute(&data);
-        Ok(format!("{:x}", digest))
+    /// Extensions this module recognizes as claiming to be media at all
+    /// (i.e. ones `detect_file_format` has a signature for). A file wearing
+    /// one of these whose header matches no signature is flagged as
+    /// non-media masquerading as media, rather than just left alone.
+    const VERIFIABLE_EXTENSIONS: &'static [&'static str] = &[
+        "mp4", "mov", "webm", "mkv", "jpg", "jpeg", "webp", "png", "gif", "bmp", "heic", "heif", "avif",
+    ];
+
+    /// The extensions a sniffed container is normally saved under, used both
+    /// to decide whether a claimed extension disagrees with it and to
+    /// suggest a replacement. Keyed on `mime_sniff::SniffedFormat::label()`.
+    fn expected_extensions_for(container: &str) -> &'static [&'static str] {
+        match container {
+            "MP4/MOV" => &["mp4", "mov"],
+            "WebM/MKV" => &["webm", "mkv"],
+            "JPEG" => &["jpg", "jpeg"],
+            "PNG" => &["png"],
+            "WebP" => &["webp"],
+            "GIF" => &["gif"],
+            "BMP" => &["bmp"],
+            "HEIC" => &["heic"],
+            "HEIF" => &["heif"],
+            "AVIF" => &["avif"],
+            _ => &[],
+        }
     }
 
-    fn get_image_info(&self, filepath: &Path) -> (Option<(u32, u32)>, Option<String>) {
+    /// Compares `filepath`'s extension against `sniffed_format` (the
+    /// container `detect_file_format` actually found in the header).
+    /// Flags both directions of mismatch: real media saved under an
+    /// unrelated extension (e.g. a `.txt` that's really an MP4), and a
+    /// verifiable media extension whose header doesn't match any known
+    /// signature at all (e.g. a `.mp4` that's really plain text).
+    fn verify_extension(filepath: &Path, sniffed_format: &Option<String>) -> Option<ExtensionMismatch> {
+        let claimed_extension = filepath.extension().and_then(|ext| ext.to_str())?.to_lowercase();
+
+        match sniffed_format {
+            Some(container) => {
+                let expected = Self::expected_extensions_for(container.as_str());
+                if expected.is_empty() || expected.contains(&claimed_extension.as_str()) {
+                    None
+                } else {
+                    Some(ExtensionMismatch {
+                        claimed_extension,
+                        sniffed_container: container.clone(),
+                        suggested_extension: expected[0].to_string(),
+                    })
+                }
+            }
+            None if Self::VERIFIABLE_EXTENSIONS.contains(&claimed_extension.as_str()) => Some(ExtensionMismatch {
+                claimed_extension,
+                sniffed_container: "unrecognized (no known media signature)".to_string(),
+                suggested_extension: String::new(),
+            }),
+            None => None,
+        }
+    }
+
+    /// Extensions handled by the pure-Rust ISO-BMFF box parser in
+    /// `video_container`. Deliberately excludes `avi`: real AVI files are
+    /// RIFF-based rather than box-based, so `probe_container` could never
+    /// return anything for one and reading it in full would just waste I/O.
+    fn is_video_extension(filepath: &Path) -> bool {
+        matches!(
+            filepath.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase).as_deref(),
+            Some("mp4") | Some("mov")
+        )
+    }
+
+    fn calculate_md5(data: &[u8]) -> String {
+        let digest = md5::compute(data);
+        format!("{:x}", digest)
+    }
+
+    /// `decoded_image` lets callers that already decoded the file (see
+    /// `analyze_file_with_options`) share that decode here instead of this
+    /// function re-opening the file itself as its fallback path.
+    fn get_image_info(&self, filepath: &Path, decoded_image: Option<&image::DynamicImage>) -> (Option<(u32, u32)>, Option<String>) {
         // First detect the actual file format by reading file header
         let actual_format = self.detect_file_format(filepath);
-        
+
         // Try imagesize crate first (handles more formats reliably)
         if let Ok(size) = imagesize::size(filepath) {
             return (Some((size.width as u32, size.height as u32)), actual_format);
         }
-        
-        // Fallback to image crate
-        if let Ok(img) = image::open(filepath) {
+
+        // Fallback to the already-decoded image, if we have one
+        if let Some(img) = decoded_image {
             let (width, height) = img.dimensions();
             let detected_format = format!("{:?}", img.color());
             (Some((width, height)), actual_format.or(Some(detected_format)))
@@ -144,63 +338,23 @@ impl MetadataManager {
         }
     }
 
+    /// Delegates to `mime_sniff`, which covers more formats (GIF, BMP,
+    /// HEIC/HEIF/AVIF) and - unlike the old inline header check this used to
+    /// do - tells ISO-BMFF brand families apart instead of calling every
+    /// box-based file "MP4/MOV".
     fn detect_file_format(&self, filepath: &Path) -> Option<String> {
-        use std::io::Read;
-        
-        if let Ok(mut file) = std::fs::File::open(filepath) {
-            let mut header = [0u8; 16];
-            if file.read(&mut header).is_ok() {
-                // Check for WebP signature
-                if header[0..4] == [0x52, 0x49, 0x46, 0x46] && header[8..12] == [0x57, 0x45, 0x42, 0x50] {
-                    return Some("WebP".to_string());
-                }
-                // Check for PNG signature
-                if header[0..8] == [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A] {
-                    return Some("PNG".to_string());
-                }
-                // Check for JPEG signature
-                if header[0..2] == [0xFF, 0xD8] {
-                    return Some("JPEG".to_string());
-                }
-            }
-        }
-        None
+        mime_sniff::sniff_file(filepath).map(|format| format.label().to_string())
     }
 
-    fn search_strings_in_file(&self, filepath: &Path) -> Vec<String> {
-        if let Ok(data) = fs::read(filepath) {
-            let max_search_bytes = std::cmp::min(data.len(), 1024 * 1024); // 1MB max
-            let search_data = &data[..max_search_bytes];
-            
-            let mut found_strings = Vec::new();
-            let mut current_string = String::new();
-            
-            for &byte in search_data {
-                if (32..=126).contains(&byte) { // Printable ASCII
-                    current_string.push(byte as char);
-                } else {
-                    if current_string.len() >= 4 {
-                        let lower_string = current_string.to_lowercase();
-                        if self.string_indicators.iter().any(|indicator| lower_string.contains(indicator)) {
-                            found_strings.push(current_string.clone());
-                        }
-                    }
-                    current_string.clear();
-                }
-            }
-            
-            // Check final string
-            if current_string.len() >= 4 {
-                let lower_string = current_string.to_lowercase();
-                if self.string_indicators.iter().any(|indicator| lower_string.contains(indicator)) {
-                    found_strings.push(current_string);
-                }
-            }
-            
-            found_strings
-        } else {
-            Vec::new()
-        }
+    fn search_strings_in_file(&self, data: &[u8]) -> Vec<String> {
+        let max_search_bytes = std::cmp::min(data.len(), 1024 * 1024); // 1MB max
+        video_container::extract_printable_strings(&data[..max_search_bytes])
+            .into_iter()
+            .filter(|s| {
+                let lower = s.to_lowercase();
+                self.rules.all_string_patterns.iter().any(|indicator| lower.contains(indicator.as_str()))
+            })
+            .collect()
     }
 
     fn analyze_tiktok_evidence(
@@ -209,7 +363,9 @@ impl MetadataManager {
         dimensions: &Option<(u32, u32)>,
         aspect_ratio: &Option<f64>,
         strings_found: &[String],
-        file_format: &Option<String>,
+        extension_mismatch: &Option<ExtensionMismatch>,
+        video_container: &Option<video_container::VideoContainerInfo>,
+        steg_suspected: bool,
     ) -> TikTokEvidence {
         let mut evidence = TikTokEvidence {
             is_tiktok: false,
@@ -220,10 +376,9 @@ impl MetadataManager {
         };
 
         // Exclusion: If metadata contains camera photo indicators, set confidence to -1000
-        let camera_keywords = ["Focal Length", "ISO", "Aperture"];
         let is_camera_photo = strings_found.iter().any(|s| {
             let lower = s.to_lowercase();
-            camera_keywords.iter().any(|kw| lower.contains(kw))
+            self.rules.camera_exclusion_keywords.iter().any(|kw| lower.contains(kw.as_str()))
         });
         if is_camera_photo {
             evidence.evidence_found.push("Camera photo metadata detected (focal length, ISO, or aperture)".to_string());
@@ -234,36 +389,64 @@ impl MetadataManager {
             return evidence;
         }
 
-        // 1. Check for AIGC metadata in strings
-        if strings_found.iter().any(|s| s.to_lowercase().contains("aigc_label_type")) {
-            evidence.evidence_found.push("AIGC metadata found".to_string());
-            evidence.indicators.insert("aigc_metadata".to_string(), "detected".to_string());
-            evidence.confidence_score += 40;
+        // Exclusion: the extension claims a media container this module knows
+        // how to sniff, but the header didn't match any known signature at
+        // all. That's not a renamed TikTok export, just a non-media file
+        // wearing a media extension, so drop it below the detection
+        // threshold instead of scoring it on filename/dimension heuristics.
+        if let Some(mismatch) = extension_mismatch {
+            if mismatch.suggested_extension.is_empty() {
+                evidence.evidence_found.push(format!(
+                    "Claims .{} but header matches no known media signature",
+                    mismatch.claimed_extension
+                ));
+                evidence.indicators.insert("extension_mismatch".to_string(), "non_media".to_string());
+                evidence.confidence_score = 0;
+                evidence.verdict = "EXCLUDED: Extension claims media but header is not a recognized container".to_string();
+                evidence.is_tiktok = false;
+                return evidence;
+            }
+        }
+
+        // 1. Check the loaded string-indicator rules (AIGC metadata, the
+        // ByteDance content hash, TikTok/Douyin/ByteDance brand names, and
+        // whatever else a custom ruleset adds) against the file's extracted
+        // strings. Each rule scores at most once, the first time any of its
+        // patterns is found.
+        for rule in &self.rules.string_indicators {
+            let matched: Vec<&String> = strings_found.iter()
+                .filter(|s| {
+                    let lower = s.to_lowercase();
+                    rule.patterns.iter().any(|pattern| lower.contains(pattern.as_str()))
+                })
+                .collect();
+
+            if !matched.is_empty() {
+                evidence.evidence_found.push(rule.label.clone());
+                evidence.indicators.insert(
+                    rule.label.clone(),
+                    matched.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", "),
+                );
+                evidence.confidence_score += rule.points;
+            }
         }
 
         // 2. Check for TikTok video IDs
         for string in strings_found {
-            if self.tiktok_video_id_regex.is_match(string) {
+            if self.rules.video_id_regex.is_match(string) {
                 evidence.evidence_found.push("TikTok video ID found".to_string());
                 evidence.indicators.insert("tiktok_video_id".to_string(), string.clone());
-                evidence.confidence_score += 35;
+                evidence.confidence_score += self.rules.video_id_points;
                 break;
             }
         }
 
-        // 3. Check for vid_md5 (ByteDance content hash)
-        if strings_found.iter().any(|s| s.to_lowercase().contains("vid_md5")) {
-            evidence.evidence_found.push("ByteDance content hash found".to_string());
-            evidence.indicators.insert("vid_md5".to_string(), "detected".to_string());
-            evidence.confidence_score += 30;
-        }
-
-        // 4. Check video dimensions
+        // 3. Check video dimensions
         if let Some((width, height)) = dimensions {
-            if self.tiktok_dimensions.contains(&(*width, *height)) {
+            if self.rules.tiktok_dimensions.contains(&(*width, *height)) {
                 evidence.evidence_found.push(format!("TikTok-typical dimensions: {}x{}", width, height));
                 evidence.indicators.insert("video_dimensions".to_string(), format!("{}x{}", width, height));
-                evidence.confidence_score += 25;
+                evidence.confidence_score += self.rules.dimension_points;
             }
 
             // Check for 9:16 aspect ratio (TikTok standard)
@@ -282,53 +465,73 @@ impl MetadataManager {
             }
         }
 
-        // 5. Check for WebP format with PNG extension (TikTok app behavior)
-        if filename.to_lowercase().ends_with(".png") {
-            if let Some(format) = file_format {
-                if format.to_lowercase().contains("webp") {
-                    evidence.evidence_found.push("WebP format with PNG extension (TikTok app behavior)".to_string());
-                    evidence.indicators.insert("format_mismatch".to_string(), "webp_as_png".to_string());
-                    evidence.confidence_score += 15;
-                }
+        // 4. Check for a real media container under the wrong extension
+        // (TikTok exports are often renamed on-device, e.g. a WebP saved
+        // with a `.png` extension, or a video saved as `.txt`)
+        if let Some(mismatch) = extension_mismatch {
+            if !mismatch.suggested_extension.is_empty() {
+                evidence.evidence_found.push(format!(
+                    "Claims .{} but header is {} (likely renamed media)",
+                    mismatch.claimed_extension, mismatch.sniffed_container
+                ));
+                evidence.indicators.insert("extension_mismatch".to_string(), mismatch.sniffed_container.clone());
+                evidence.confidence_score += 25;
             }
         }
 
-        // 6. Check for hash-based filename (32 chars + extension)
-        if filename.len() == 36 && filename.matches('.').count() == 1 {
-            let name_part = filename.split('.').next().unwrap_or("");
-            if name_part.len() == 32 && name_part.chars().all(|c| c.is_ascii_hexdigit()) {
-                evidence.evidence_found.push("MD5-like hash filename (app-generated)".to_string());
-                evidence.indicators.insert("filename_pattern".to_string(), "md5_hash".to_string());
-                evidence.confidence_score += 10;
+        // 5. Check filename patterns (e.g. the built-in MD5-like hash
+        // filename check), loaded from the same ruleset as the string
+        // indicators above.
+        for rule in &self.rules.filename_patterns {
+            if rule.regex.is_match(filename) {
+                evidence.evidence_found.push(rule.label.clone());
+                evidence.indicators.insert(rule.label.clone(), filename.to_string());
+                evidence.confidence_score += rule.points;
             }
         }
 
-        // 7. Check string analysis for TikTok indicators
-        if !strings_found.is_empty() {
-            let tiktok_strings: Vec<&String> = strings_found.iter()
-                .filter(|s| {
-                    let lower = s.to_lowercase();
-                    ["tiktok", "douyin", "bytedance", "musically"].iter()
-                        .any(|indicator| lower.contains(indicator))
-                })
-                .collect();
-
-            if !tiktok_strings.is_empty() {
-                evidence.evidence_found.push("TikTok strings found in file".to_string());
-                evidence.indicators.insert("string_indicators".to_string(), 
-                    tiktok_strings.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", "));
+        // 6. Check the MP4/MOV container's ftyp brand list for a TikTok/ByteDance
+        // marker (the udta tag strings were already folded into `strings_found`
+        // above, so rule 1 covers those; the brand codes are short 4-character
+        // tokens that need their own substring check against the same markers).
+        if let Some(container) = video_container {
+            let mut brands = container.compatible_brands.clone();
+            brands.extend(container.major_brand.clone());
+            let matched_brand = brands.iter().find(|brand| {
+                let lower = brand.to_lowercase();
+                // A 4-character fourCC can't literally contain a longer
+                // marker like "tiktok", so check both directions: the brand
+                // containing the marker (unlikely, but costs nothing to
+                // check) and the marker containing the brand, for the more
+                // plausible case of a short/abbreviated custom brand code.
+                self.rules.all_string_patterns.iter().any(|pattern| lower.contains(pattern.as_str()) || pattern.contains(lower.as_str()))
+            });
+            if let Some(brand) = matched_brand {
+                evidence.evidence_found.push(format!("TikTok/ByteDance marker found in container brand \"{}\"", brand));
+                evidence.indicators.insert("video_container_brand".to_string(), brand.clone());
                 evidence.confidence_score += 20;
             }
         }
 
-        // Determine verdict based on confidence score
-        if evidence.confidence_score >= 70 {
+        // 7. LSB steganalysis: a suspiciously random-looking bit plane on its
+        // own is weak evidence (busy/noisy photos can trip it too), so this
+        // carries less weight than the stronger string/brand-based rules
+        // above.
+        if steg_suspected {
+            evidence.evidence_found.push("LSB plane looks like hidden payload data (steganalysis)".to_string());
+            evidence.indicators.insert("steg_suspected".to_string(), "true".to_string());
+            evidence.confidence_score += 15;
+        }
+
+        // Determine verdict based on the loaded confidence thresholds
+        let thresholds = &self.rules.thresholds;
+        if evidence.confidence_score >= thresholds.confirmed {
             evidence.is_tiktok = true;
             evidence.verdict = "CONFIRMED: File is from TikTok".to_string();
-        } else if evidence.confidence_score >= 40 {
+        } else if evidence.confidence_score >= thresholds.likely {
             evidence.is_tiktok = true;
             evidence.verdict = "LIKELY: Strong evidence suggests TikTok origin".to_string();
-        } else if evidence.confidence_score >= 20 {
+        } else if evidence.confidence_score >= thresholds.possible {
             evidence.verdict = "POSSIBLE: Some TikTok-like characteristics found".to_string();
         } else {
             evidence.verdict = "UNLIKELY: No significant TikTok evidence found".to_string();
@@ -338,29 +541,54 @@ impl MetadataManager {
     }
 
     pub fn analyze_folder(&self, folder_path: &Path) -> Result<Vec<FileMetadata>> {
-        let mut results = Vec::new();
-        
+        self.analyze_folder_with_options(folder_path, true)
+    }
+
+    /// Same as `analyze_folder`, but walks the tree once into a `Vec` and
+    /// then analyzes the collected files with a rayon parallel iterator
+    /// instead of one at a time, and can skip the MD5 digest
+    /// (`compute_md5 = false`) for a faster dimensions-and-strings-only pass
+    /// over a large camera roll. Results are sorted by filepath afterward so
+    /// the order is deterministic regardless of which worker finished first.
+    pub fn analyze_folder_with_options(&self, folder_path: &Path, compute_md5: bool) -> Result<Vec<FileMetadata>> {
         if !folder_path.is_dir() {
             return Err(anyhow::anyhow!("Path is not a directory"));
         }
 
-        for entry in walkdir::WalkDir::new(folder_path) {
-            let entry = entry?;
-            let path = entry.path();
-            
-            if path.is_file() {
-                if let Some(ext) = path.extension() {
-                    let ext_str = ext.to_str().unwrap_or("").to_lowercase();
-                    if ["jpg", "jpeg", "png", "webp", "gif", "bmp", "mp4", "mov", "avi"]
-                        .contains(&ext_str.as_str()) {
-                        match self.analyze_file(path) {
-                            Ok(metadata) => results.push(metadata),
-                            Err(e) => eprintln!("Error analyzing {}: {}", path.display(), e),
-                        }
-                    }
+        let media_files: Vec<PathBuf> = walkdir::WalkDir::new(folder_path)
+            .into_iter()
+            .filter_map(|entry| match entry {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    eprintln!("Error walking directory entry: {}", e);
+                    None
                 }
-            }
-        }
+            })
+            .map(|entry| entry.into_path())
+            .filter(|path| {
+                path.is_file()
+                    && path.extension()
+                        .and_then(|ext| ext.to_str())
+                        .map(|ext| {
+                            ["jpg", "jpeg", "png", "webp", "gif", "bmp", "heic", "heif", "avif", "mp4", "mov", "avi"]
+                                .contains(&ext.to_lowercase().as_str())
+                        })
+                        .unwrap_or(false)
+            })
+            .collect();
+
+        let mut results: Vec<FileMetadata> = media_files
+            .par_iter()
+            .filter_map(|path| match self.analyze_file_with_options(path, compute_md5) {
+                Ok(metadata) => Some(metadata),
+                Err(e) => {
+                    eprintln!("Error analyzing {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .collect();
+
+        results.sort_by(|a, b| a.filepath.cmp(&b.filepath));
 
         Ok(results)
     }
@@ -0,0 +1,178 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// A media container identified by sniffing a file's header bytes, rather
+/// than trusting its extension. Supersedes the old ad-hoc if-chain in
+/// `MetadataManager::detect_file_format`, which only recognized
+/// WebP/PNG/JPEG plus a single catch-all "MP4/MOV" and "WebM/MKV" bucket for
+/// anything box-based or EBML-based; this adds GIF/BMP and splits the
+/// ISO-BMFF bucket into its actual brand families (HEIC/HEIF/AVIF vs.
+/// MP4/MOV) by reading the `ftyp` major brand instead of assuming any
+/// box-based file is a video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SniffedFormat {
+    Jpeg,
+    Png,
+    Gif,
+    Bmp,
+    WebP,
+    Heic,
+    Heif,
+    Avif,
+    Mp4Mov,
+    WebmMkv,
+}
+
+impl SniffedFormat {
+    /// Human-readable label. `MetadataManager` still exposes `file_format`
+    /// as a plain `Option<String>` (it's serialized and displayed all over
+    /// `scanner.rs`/`report_export.rs`), so callers that need a `String`
+    /// convert through this rather than the enum itself propagating further.
+    pub fn label(&self) -> &'static str {
+        match self {
+            SniffedFormat::Jpeg => "JPEG",
+            SniffedFormat::Png => "PNG",
+            SniffedFormat::Gif => "GIF",
+            SniffedFormat::Bmp => "BMP",
+            SniffedFormat::WebP => "WebP",
+            SniffedFormat::Heic => "HEIC",
+            SniffedFormat::Heif => "HEIF",
+            SniffedFormat::Avif => "AVIF",
+            SniffedFormat::Mp4Mov => "MP4/MOV",
+            SniffedFormat::WebmMkv => "WebM/MKV",
+        }
+    }
+}
+
+/// Sniffs `data` (the first ~16+ bytes of a file are enough) for a known
+/// container signature. ISO-BMFF containers (an `ftyp` box at offset 4) are
+/// distinguished by their major brand at offset 8: `heic`/`heix`/`heim`/
+/// `heis`/`hevc`/`hevx` map to HEIC, `mif1`/`msf1` to HEIF, `avif`/`avis` to
+/// AVIF, anything else box-based falls back to the existing MP4/MOV bucket.
+pub fn sniff(data: &[u8]) -> Option<SniffedFormat> {
+    if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        return Some(SniffedFormat::WebP);
+    }
+    if data.len() >= 8 && data[0..8] == [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A] {
+        return Some(SniffedFormat::Png);
+    }
+    if data.len() >= 2 && data[0..2] == [0xFF, 0xD8] {
+        return Some(SniffedFormat::Jpeg);
+    }
+    if data.len() >= 6 && (&data[0..6] == b"GIF87a" || &data[0..6] == b"GIF89a") {
+        return Some(SniffedFormat::Gif);
+    }
+    if data.len() >= 2 && &data[0..2] == b"BM" {
+        return Some(SniffedFormat::Bmp);
+    }
+    if data.len() >= 12 && &data[4..8] == b"ftyp" {
+        return Some(match &data[8..12] {
+            b"heic" | b"heix" | b"heim" | b"heis" | b"hevc" | b"hevx" => SniffedFormat::Heic,
+            b"mif1" | b"msf1" => SniffedFormat::Heif,
+            b"avif" | b"avis" => SniffedFormat::Avif,
+            _ => SniffedFormat::Mp4Mov,
+        });
+    }
+    if data.len() >= 4 && data[0..4] == [0x1A, 0x45, 0xDF, 0xA3] {
+        return Some(SniffedFormat::WebmMkv);
+    }
+    None
+}
+
+/// Convenience wrapper for callers that don't already have the file's bytes
+/// in memory: reads just enough of the header to sniff and discards the
+/// rest.
+pub fn sniff_file(filepath: &Path) -> Option<SniffedFormat> {
+    let mut file = File::open(filepath).ok()?;
+    let mut header = [0u8; 16];
+    let n = file.read(&mut header).ok()?;
+    sniff(&header[..n])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+
+    fn ftyp_header(major_brand: &[u8; 4]) -> Vec<u8> {
+        let mut data = vec![0u8; 4]; // box size, irrelevant to sniffing
+        data.extend_from_slice(b"ftyp");
+        data.extend_from_slice(major_brand);
+        data
+    }
+
+    #[test]
+    fn sniffs_png_by_magic_bytes() {
+        let data = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+        assert_eq!(sniff(&data), Some(SniffedFormat::Png));
+    }
+
+    #[test]
+    fn sniffs_jpeg_by_magic_bytes() {
+        let data = [0xFF, 0xD8, 0xFF, 0xE0];
+        assert_eq!(sniff(&data), Some(SniffedFormat::Jpeg));
+    }
+
+    #[test]
+    fn sniffs_gif_both_version_signatures() {
+        assert_eq!(sniff(b"GIF87a"), Some(SniffedFormat::Gif));
+        assert_eq!(sniff(b"GIF89a"), Some(SniffedFormat::Gif));
+    }
+
+    #[test]
+    fn sniffs_bmp_by_magic_bytes() {
+        assert_eq!(sniff(b"BM    "), Some(SniffedFormat::Bmp));
+    }
+
+    #[test]
+    fn sniffs_webp_by_riff_container() {
+        let mut data = b"RIFF".to_vec();
+        data.extend_from_slice(&[0, 0, 0, 0]); // chunk size, irrelevant
+        data.extend_from_slice(b"WEBP");
+        assert_eq!(sniff(&data), Some(SniffedFormat::WebP));
+    }
+
+    #[test]
+    fn sniffs_webm_mkv_by_ebml_header() {
+        let data = [0x1A, 0x45, 0xDF, 0xA3];
+        assert_eq!(sniff(&data), Some(SniffedFormat::WebmMkv));
+    }
+
+    #[test]
+    fn splits_isobmff_major_brands_into_heic_heif_avif_and_mp4mov() {
+        assert_eq!(sniff(&ftyp_header(b"heic")), Some(SniffedFormat::Heic));
+        assert_eq!(sniff(&ftyp_header(b"hevc")), Some(SniffedFormat::Heic));
+        assert_eq!(sniff(&ftyp_header(b"mif1")), Some(SniffedFormat::Heif));
+        assert_eq!(sniff(&ftyp_header(b"avif")), Some(SniffedFormat::Avif));
+        assert_eq!(sniff(&ftyp_header(b"isom")), Some(SniffedFormat::Mp4Mov));
+        assert_eq!(sniff(&ftyp_header(b"mp42")), Some(SniffedFormat::Mp4Mov));
+    }
+
+    #[test]
+    fn returns_none_for_unrecognized_or_too_short_data() {
+        assert_eq!(sniff(b"not a known format"), None);
+        assert_eq!(sniff(&[]), None);
+        assert_eq!(sniff(&[0xFF]), None);
+    }
+
+    #[test]
+    fn label_returns_the_expected_human_readable_name() {
+        assert_eq!(SniffedFormat::Mp4Mov.label(), "MP4/MOV");
+        assert_eq!(SniffedFormat::Heic.label(), "HEIC");
+    }
+
+    #[test]
+    fn sniff_file_reads_header_bytes_from_disk() {
+        let path = std::env::temp_dir().join(format!("mime_sniff_test_{}.png", std::process::id()));
+        let mut file = fs::File::create(&path).unwrap();
+        file.write_all(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]).unwrap();
+        drop(file);
+
+        let result = sniff_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(result, Some(SniffedFormat::Png));
+    }
+}
@@ -0,0 +1,328 @@
+use std::fs;
+use std::path::Path;
+use anyhow::{Result, Context, bail};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// A group of case-insensitive substring patterns that score together as one
+/// piece of evidence (e.g. the original hardcoded "tiktok"/"douyin"/
+/// "bytedance"/"musically" check, which scored 20 points total no matter how
+/// many of the four matched). `points` is awarded at most once per rule, the
+/// first time any of `patterns` is found in a file's extracted strings.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StringIndicatorRule {
+    pub label: String,
+    pub patterns: Vec<String>,
+    pub points: u32,
+}
+
+/// A regex checked against a file's name (not its contents), such as the
+/// original hardcoded "32 hex characters plus an extension" MD5-style
+/// filename check.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FilenamePatternRule {
+    pub label: String,
+    pub pattern: String,
+    pub points: u32,
+}
+
+/// Confidence-score cutoffs for each verdict tier, mirroring the thresholds
+/// `report_export::tier_for` uses for the CLI/export-facing "confirmed/
+/// likely/possible/unlikely" tiers.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct VerdictThresholds {
+    pub confirmed: u32,
+    pub likely: u32,
+    pub possible: u32,
+}
+
+/// The serializable form of a detection ruleset, as loaded from a TOML or
+/// JSON file on disk. Regex patterns are kept as plain strings here (`Regex`
+/// itself isn't `Deserialize`/`Serialize`) - see `RuleSet::compile` for the
+/// one-time compile step that turns this into the form
+/// `MetadataManager::analyze_tiktok_evidence` actually evaluates.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RawRuleSet {
+    pub tiktok_dimensions: Vec<(u32, u32)>,
+    pub dimension_points: u32,
+    pub video_id_pattern: String,
+    pub video_id_points: u32,
+    pub string_indicators: Vec<StringIndicatorRule>,
+    pub camera_exclusion_keywords: Vec<String>,
+    pub filename_patterns: Vec<FilenamePatternRule>,
+    pub thresholds: VerdictThresholds,
+}
+
+impl RawRuleSet {
+    /// The ruleset equivalent to what `MetadataManager::new` used to
+    /// hardcode before detection rules became loadable. `RuleSet::built_in`
+    /// is what callers should reach for; this exists mainly so `built_in`
+    /// has something to compile and so a user customizing a loaded ruleset
+    /// has a known-good starting point to diff against.
+    pub fn built_in() -> Self {
+        RawRuleSet {
+            tiktok_dimensions: vec![
+                (576, 1024), (576, 1246), (576, 1280),
+                (1080, 1920), (1080, 1800), (1080, 2340), (1080, 2400),
+                (828, 1792), (750, 1334), (1125, 2436), (1242, 2688),
+                (1284, 2778), (1170, 2532),
+            ],
+            dimension_points: 25,
+            video_id_pattern: r"vid:v\d+g[fl]0000[a-f0-9]+".to_string(),
+            video_id_points: 35,
+            string_indicators: vec![
+                StringIndicatorRule {
+                    label: "AIGC metadata found".to_string(),
+                    patterns: vec!["aigc_label_type".to_string()],
+                    points: 40,
+                },
+                StringIndicatorRule {
+                    label: "ByteDance content hash found".to_string(),
+                    patterns: vec!["vid_md5".to_string()],
+                    points: 30,
+                },
+                StringIndicatorRule {
+                    label: "TikTok strings found in file".to_string(),
+                    patterns: vec![
+                        "tiktok".to_string(),
+                        "douyin".to_string(),
+                        "bytedance".to_string(),
+                        "musically".to_string(),
+                    ],
+                    points: 20,
+                },
+            ],
+            camera_exclusion_keywords: vec![
+                "focal length".to_string(),
+                "iso".to_string(),
+                "aperture".to_string(),
+            ],
+            filename_patterns: vec![
+                FilenamePatternRule {
+                    label: "MD5-like hash filename (app-generated)".to_string(),
+                    pattern: r"^[0-9a-fA-F]{32}\.[^.]{3}$".to_string(),
+                    points: 10,
+                },
+            ],
+            thresholds: VerdictThresholds { confirmed: 70, likely: 40, possible: 20 },
+        }
+    }
+
+    /// Loads a ruleset from a TOML or JSON file, picking the format from
+    /// `path`'s extension (`.json` for JSON, anything else is parsed as
+    /// TOML).
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read ruleset file: {}", path.display()))?;
+
+        if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse ruleset as JSON: {}", path.display()))
+        } else {
+            toml::from_str(&content)
+                .with_context(|| format!("Failed to parse ruleset as TOML: {}", path.display()))
+        }
+    }
+}
+
+/// A compiled, ready-to-evaluate `StringIndicatorRule`.
+pub struct CompiledStringIndicator {
+    pub label: String,
+    pub patterns: Vec<String>,
+    pub points: u32,
+}
+
+/// A compiled, ready-to-evaluate `FilenamePatternRule`.
+pub struct CompiledFilenamePattern {
+    pub label: String,
+    pub regex: Regex,
+    pub points: u32,
+}
+
+/// The detection rules `MetadataManager::analyze_tiktok_evidence` iterates,
+/// compiled once from a `RawRuleSet` (see `compile`) rather than re-parsed
+/// or recompiled per file. Replaces the fixed branches that used to live
+/// directly in `MetadataManager::new`/`analyze_tiktok_evidence`, so power
+/// users can tune weights, add new ByteDance markers, or share a ruleset
+/// without recompiling the scanner.
+pub struct RuleSet {
+    pub tiktok_dimensions: Vec<(u32, u32)>,
+    pub dimension_points: u32,
+    pub video_id_regex: Regex,
+    pub video_id_points: u32,
+    pub string_indicators: Vec<CompiledStringIndicator>,
+    /// Every pattern across every rule in `string_indicators`, flattened
+    /// once at compile time. Callers that just need to know whether a
+    /// string matches *some* indicator (narrowing extracted strings,
+    /// checking a container's brand codes) use this instead of re-flattening
+    /// `string_indicators` themselves on every file.
+    pub all_string_patterns: Vec<String>,
+    pub camera_exclusion_keywords: Vec<String>,
+    pub filename_patterns: Vec<CompiledFilenamePattern>,
+    pub thresholds: VerdictThresholds,
+}
+
+impl RuleSet {
+    /// Compiles every regex in `raw` exactly once, up front, instead of
+    /// lazily recompiling a pattern string on each file analyzed.
+    pub fn compile(raw: RawRuleSet) -> Result<Self> {
+        if raw.string_indicators.iter().any(|rule| rule.patterns.is_empty()) {
+            bail!("A string indicator rule must have at least one pattern");
+        }
+
+        let thresholds = raw.thresholds;
+        if !(thresholds.confirmed >= thresholds.likely && thresholds.likely >= thresholds.possible) {
+            bail!(
+                "Verdict thresholds must satisfy confirmed >= likely >= possible, got confirmed={}, likely={}, possible={}",
+                thresholds.confirmed, thresholds.likely, thresholds.possible
+            );
+        }
+
+        let video_id_regex = Regex::new(&raw.video_id_pattern)
+            .with_context(|| format!("Failed to compile video ID pattern: {}", raw.video_id_pattern))?;
+
+        let filename_patterns = raw.filename_patterns.into_iter()
+            .map(|rule| {
+                let regex = Regex::new(&rule.pattern)
+                    .with_context(|| format!("Failed to compile filename pattern \"{}\" ({})", rule.pattern, rule.label))?;
+                Ok(CompiledFilenamePattern { label: rule.label, regex, points: rule.points })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let string_indicators: Vec<CompiledStringIndicator> = raw.string_indicators.into_iter()
+            .map(|rule| CompiledStringIndicator {
+                label: rule.label,
+                patterns: rule.patterns.into_iter().map(|p| p.to_lowercase()).collect(),
+                points: rule.points,
+            })
+            .collect();
+
+        let all_string_patterns = string_indicators.iter()
+            .flat_map(|rule| rule.patterns.iter().cloned())
+            .collect();
+
+        Ok(RuleSet {
+            tiktok_dimensions: raw.tiktok_dimensions,
+            dimension_points: raw.dimension_points,
+            video_id_regex,
+            video_id_points: raw.video_id_points,
+            string_indicators,
+            all_string_patterns,
+            camera_exclusion_keywords: raw.camera_exclusion_keywords.into_iter().map(|kw| kw.to_lowercase()).collect(),
+            filename_patterns,
+            thresholds: raw.thresholds,
+        })
+    }
+
+    /// The ruleset equivalent to what `MetadataManager::new` used to
+    /// hardcode. Infallible: `RawRuleSet::built_in`'s patterns are all
+    /// known-valid regexes.
+    pub fn built_in() -> Self {
+        Self::compile(RawRuleSet::built_in()).expect("built-in ruleset patterns are valid regexes")
+    }
+
+    /// Loads and compiles a ruleset from a TOML or JSON file in one step.
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::compile(RawRuleSet::load(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_ruleset_compiles_successfully() {
+        let rule_set = RuleSet::built_in();
+        assert!(!rule_set.string_indicators.is_empty());
+        assert!(!rule_set.filename_patterns.is_empty());
+        assert!(rule_set.video_id_regex.is_match("vid:v12000gf0000abcdef01"));
+    }
+
+    #[test]
+    fn compile_flattens_string_indicator_patterns_to_lowercase() {
+        let mut raw = RawRuleSet::built_in();
+        raw.string_indicators = vec![StringIndicatorRule {
+            label: "Mixed case marker".to_string(),
+            patterns: vec!["TikTok".to_string(), "ByteDance".to_string()],
+            points: 10,
+        }];
+
+        let rule_set = RuleSet::compile(raw).expect("valid ruleset should compile");
+        assert_eq!(rule_set.all_string_patterns, vec!["tiktok".to_string(), "bytedance".to_string()]);
+    }
+
+    #[test]
+    fn compile_rejects_string_indicator_rule_with_no_patterns() {
+        let mut raw = RawRuleSet::built_in();
+        raw.string_indicators = vec![StringIndicatorRule {
+            label: "Empty rule".to_string(),
+            patterns: vec![],
+            points: 10,
+        }];
+
+        let err = RuleSet::compile(raw).expect_err("a rule with no patterns should be rejected");
+        assert!(err.to_string().contains("at least one pattern"));
+    }
+
+    #[test]
+    fn compile_rejects_out_of_order_verdict_thresholds() {
+        let mut raw = RawRuleSet::built_in();
+        raw.thresholds = VerdictThresholds { confirmed: 10, likely: 40, possible: 20 };
+
+        let err = RuleSet::compile(raw).expect_err("thresholds must be non-increasing");
+        assert!(err.to_string().contains("Verdict thresholds"));
+    }
+
+    #[test]
+    fn compile_rejects_invalid_video_id_regex() {
+        let mut raw = RawRuleSet::built_in();
+        raw.video_id_pattern = "[unclosed".to_string();
+
+        assert!(RuleSet::compile(raw).is_err());
+    }
+
+    #[test]
+    fn compile_rejects_invalid_filename_pattern_regex() {
+        let mut raw = RawRuleSet::built_in();
+        raw.filename_patterns = vec![FilenamePatternRule {
+            label: "Broken pattern".to_string(),
+            pattern: "[unclosed".to_string(),
+            points: 10,
+        }];
+
+        assert!(RuleSet::compile(raw).is_err());
+    }
+
+    #[test]
+    fn load_parses_toml_ruleset_from_disk() {
+        let raw = RawRuleSet::built_in();
+        let toml_content = toml::to_string(&raw).expect("built-in ruleset should serialize to TOML");
+        let path = std::env::temp_dir().join(format!("rule_config_test_{}.toml", std::process::id()));
+        fs::write(&path, toml_content).unwrap();
+
+        let loaded = RuleSet::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(loaded.is_ok());
+    }
+
+    #[test]
+    fn load_parses_json_ruleset_from_disk() {
+        let raw = RawRuleSet::built_in();
+        let json_content = serde_json::to_string(&raw).expect("built-in ruleset should serialize to JSON");
+        let path = std::env::temp_dir().join(format!("rule_config_test_{}.json", std::process::id()));
+        fs::write(&path, json_content).unwrap();
+
+        let loaded = RuleSet::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(loaded.is_ok());
+    }
+
+    #[test]
+    fn load_surfaces_an_error_for_a_missing_file() {
+        let path = std::env::temp_dir().join(format!("rule_config_test_missing_{}.toml", std::process::id()));
+        assert!(RawRuleSet::load(&path).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use image::{DynamicImage, GenericImageView};
+
+/// Entropy (bits/bit) above which the LSB plane looks closer to random
+/// payload bits than a natural image's least-significant bits usually do.
+const ENTROPY_SUSPECT_THRESHOLD: f64 = 0.97;
+
+/// Normalized chi-square statistic (see `pair_chi_square`) at or below which
+/// adjacent value pairs in the channel histogram look suspiciously
+/// equalized, as LSB embedding tends to do.
+const CHI_SQUARE_SUSPECT_THRESHOLD: f64 = 20.0;
+
+/// Result of a lightweight LSB (least-significant-bit) steganalysis pass - a
+/// heuristic guess at whether an image carries a hidden payload smuggled
+/// into its pixel data, not a forensic-grade detector. Both measures are
+/// classic, simple steganalysis building blocks (bit-plane entropy and the
+/// Westfeld "pair of values" chi-square attack); `suspected` only trips when
+/// both agree, to keep noisy/busy photos from tripping one measure alone.
+#[derive(Debug, Clone, Copy)]
+pub struct StegResult {
+    /// Shannon entropy of the combined R/G/B least-significant-bit plane.
+    pub lsb_entropy: f64,
+    /// Normalized chi-square statistic from the pair-of-values attack.
+    pub chi_square: f64,
+    pub suspected: bool,
+}
+
+/// Runs the steganalysis pass over an already-decoded image.
+pub fn analyze(img: &DynamicImage) -> StegResult {
+    let rgb = img.to_rgb8();
+
+    let mut ones: u64 = 0;
+    let mut total: u64 = 0;
+    let mut histogram = [0u64; 256];
+
+    for pixel in rgb.pixels() {
+        for &channel in pixel.0.iter() {
+            if channel & 1 == 1 {
+                ones += 1;
+            }
+            total += 1;
+            histogram[channel as usize] += 1;
+        }
+    }
+
+    let lsb_entropy = bit_entropy(ones, total);
+    let chi_square = pair_chi_square(&histogram);
+    let suspected = lsb_entropy >= ENTROPY_SUSPECT_THRESHOLD && chi_square <= CHI_SQUARE_SUSPECT_THRESHOLD;
+
+    StegResult { lsb_entropy, chi_square, suspected }
+}
+
+/// Shannon entropy, in bits, of a stream of `total` bits of which `ones` are set.
+fn bit_entropy(ones: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    let p1 = ones as f64 / total as f64;
+    let p0 = 1.0 - p1;
+    [p0, p1].iter().filter(|&&p| p > 0.0).map(|&p| -p * p.log2()).sum()
+}
+
+/// The classic Westfeld/Pfitzmann "pair of values" chi-square statistic,
+/// averaged per pair rather than summed, so the result is comparable across
+/// images regardless of how many non-empty pairs they have. LSB embedding
+/// tends to equalize the frequencies of each adjacent value pair (2k, 2k+1)
+/// in the histogram, so an embedded image scores lower here than an
+/// untouched one.
+fn pair_chi_square(histogram: &[u64; 256]) -> f64 {
+    let mut chi_square = 0.0;
+    let mut pairs_counted: u32 = 0;
+
+    for k in 0..128 {
+        let a = histogram[2 * k] as f64;
+        let b = histogram[2 * k + 1] as f64;
+        let expected = (a + b) / 2.0;
+        if expected > 0.0 {
+            chi_square += (a - expected).powi(2) / expected;
+            pairs_counted += 1;
+        }
+    }
+
+    if pairs_counted == 0 {
+        f64::INFINITY
+    } else {
+        chi_square / pairs_counted as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb};
+
+    #[test]
+    fn bit_entropy_is_zero_when_all_bits_agree() {
+        assert_eq!(bit_entropy(0, 100), 0.0);
+        assert_eq!(bit_entropy(100, 100), 0.0);
+    }
+
+    #[test]
+    fn bit_entropy_is_one_bit_at_an_even_split() {
+        assert!((bit_entropy(50, 100) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bit_entropy_of_zero_bits_total_is_zero() {
+        assert_eq!(bit_entropy(0, 0), 0.0);
+    }
+
+    #[test]
+    fn pair_chi_square_is_zero_for_a_perfectly_balanced_histogram() {
+        let mut histogram = [0u64; 256];
+        for value in histogram.iter_mut() {
+            *value = 10;
+        }
+        assert_eq!(pair_chi_square(&histogram), 0.0);
+    }
+
+    #[test]
+    fn pair_chi_square_is_infinite_for_an_empty_histogram() {
+        let histogram = [0u64; 256];
+        assert_eq!(pair_chi_square(&histogram), f64::INFINITY);
+    }
+
+    #[test]
+    fn pair_chi_square_is_high_for_a_skewed_histogram() {
+        let mut histogram = [0u64; 256];
+        // Every even value common, every odd value absent: maximally unequal pairs.
+        for k in 0..128 {
+            histogram[2 * k] = 1000;
+        }
+        assert!(pair_chi_square(&histogram) > CHI_SQUARE_SUSPECT_THRESHOLD);
+    }
+
+    /// A solid-color image: every LSB is 0 and every pair is either fully
+    /// populated or fully empty, so neither measure should look suspicious.
+    #[test]
+    fn analyze_does_not_flag_a_solid_color_image() {
+        let buffer = ImageBuffer::from_fn(16, 16, |_, _| Rgb([10u8, 10, 10]));
+        let img = DynamicImage::ImageRgb8(buffer);
+
+        let result = analyze(&img);
+        assert_eq!(result.lsb_entropy, 0.0);
+        assert!(!result.suspected);
+    }
+
+    /// An image whose byte value cycles through the full 0..256 range gives a
+    /// perfectly balanced LSB split and histogram, mimicking what embedded
+    /// random payload bits look like to both measures.
+    #[test]
+    fn analyze_flags_an_image_with_balanced_lsb_and_histogram() {
+        let buffer = ImageBuffer::from_fn(256, 1, |x, _| {
+            let value = x as u8;
+            Rgb([value, value, value])
+        });
+        let img = DynamicImage::ImageRgb8(buffer);
+
+        let result = analyze(&img);
+        assert!(result.lsb_entropy >= ENTROPY_SUSPECT_THRESHOLD);
+        assert!(result.chi_square <= CHI_SQUARE_SUSPECT_THRESHOLD);
+        assert!(result.suspected);
+    }
+}
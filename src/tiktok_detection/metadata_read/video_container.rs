@@ -0,0 +1,311 @@
+/// Parsed facts from an MP4/MOV ("ISO base media file"/QuickTime) container,
+/// read by walking its box tree directly rather than shelling out to
+/// `ffprobe` (compare `video_probe.rs`, the `ffprobe`-based path used by
+/// `TikTokVideoDetector`). This gives `MetadataManager::analyze_file_with_options`,
+/// whose `get_image_info` only understands still-image formats, a way to read
+/// real dimensions and tag strings out of the video files it already queues.
+#[derive(Debug, Clone, Default)]
+pub struct VideoContainerInfo {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub major_brand: Option<String>,
+    pub compatible_brands: Vec<String>,
+    /// Printable ASCII strings of at least 4 characters found inside the
+    /// `moov/udta` atom (encoder tags, app identifiers, ...), extracted the
+    /// same way `MetadataManager::search_strings_in_file` scans the whole
+    /// file, just scoped to this one atom's bytes.
+    pub udta_strings: Vec<String>,
+}
+
+/// One ISO-BMFF box header, with the payload bounds already resolved
+/// relative to the buffer it was read from.
+struct BoxHeader {
+    box_type: [u8; 4],
+    payload_start: usize,
+    payload_end: usize,
+}
+
+/// Walks the sequential box headers in `data` (the whole file, or one box's
+/// payload when descending), per the ISO-BMFF layout: a 4-byte big-endian
+/// size, a 4-byte type, and a following 8-byte `largesize` when `size == 1`.
+/// `size == 0` means "this box extends to the end of `data`". Stops (rather
+/// than erroring) at the first malformed or truncated header, since a
+/// partial read should surface whatever boxes were found so far instead of
+/// discarding them.
+fn read_boxes(data: &[u8]) -> Vec<BoxHeader> {
+    let mut boxes = Vec::new();
+    let mut offset = 0;
+
+    while offset + 8 <= data.len() {
+        let size32 = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap());
+        let mut box_type = [0u8; 4];
+        box_type.copy_from_slice(&data[offset + 4..offset + 8]);
+
+        let (header_len, box_size) = if size32 == 1 {
+            if offset + 16 > data.len() {
+                break;
+            }
+            let largesize = u64::from_be_bytes(data[offset + 8..offset + 16].try_into().unwrap());
+            (16usize, largesize as usize)
+        } else if size32 == 0 {
+            (8usize, data.len() - offset)
+        } else {
+            (8usize, size32 as usize)
+        };
+
+        // `box_size` came from file bytes (including a possible 64-bit
+        // `largesize`), so a corrupt or adversarial file can claim a value
+        // that overflows `usize` arithmetic below — check with `checked_add`
+        // rather than `+` so that case is rejected instead of panicking.
+        let Some(box_end) = offset.checked_add(box_size) else {
+            break;
+        };
+        if box_size < header_len || box_end > data.len() {
+            break;
+        }
+
+        boxes.push(BoxHeader {
+            box_type,
+            payload_start: offset + header_len,
+            payload_end: box_end,
+        });
+
+        offset = box_end;
+    }
+
+    boxes
+}
+
+/// Scans `data` for printable-ASCII runs of at least 4 characters, without
+/// filtering by keyword so callers can match their own markers against the
+/// full list. Shared with `MetadataManager::search_strings_in_file`, which
+/// applies its own indicator filter on top, so the extraction rule itself
+/// (the ASCII range and minimum run length) lives in exactly one place.
+pub(crate) fn extract_printable_strings(data: &[u8]) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut current = String::new();
+
+    for &byte in data {
+        if (32..=126).contains(&byte) {
+            current.push(byte as char);
+        } else {
+            if current.len() >= 4 {
+                found.push(current.clone());
+            }
+            current.clear();
+        }
+    }
+    if current.len() >= 4 {
+        found.push(current);
+    }
+
+    found
+}
+
+/// Reads the track width/height out of a `tkhd` box payload. After the
+/// 1-byte version + 3-byte flags, both the version-0 (32-bit fields) and
+/// version-1 (64-bit fields) layouts place width/height as 16.16
+/// fixed-point values in the box's final 8 bytes, so the offset from the
+/// end is version-independent.
+fn read_tkhd_dimensions(payload: &[u8]) -> Option<(u32, u32)> {
+    if payload.len() < 8 {
+        return None;
+    }
+    let width_fixed = u32::from_be_bytes(payload[payload.len() - 8..payload.len() - 4].try_into().ok()?);
+    let height_fixed = u32::from_be_bytes(payload[payload.len() - 4..].try_into().ok()?);
+
+    let width = width_fixed >> 16;
+    let height = height_fixed >> 16;
+
+    if width == 0 || height == 0 {
+        None
+    } else {
+        Some((width, height))
+    }
+}
+
+/// Descends `moov -> trak -> tkhd` for the first track carrying dimensions,
+/// and `moov -> udta` for printable tag strings.
+fn read_moov(payload: &[u8]) -> (Option<(u32, u32)>, Vec<String>) {
+    let mut dimensions = None;
+    let mut udta_strings = Vec::new();
+
+    for child in read_boxes(payload) {
+        match &child.box_type {
+            b"trak" if dimensions.is_none() => {
+                let trak_payload = &payload[child.payload_start..child.payload_end];
+                for grandchild in read_boxes(trak_payload) {
+                    if &grandchild.box_type == b"tkhd" {
+                        dimensions = read_tkhd_dimensions(&trak_payload[grandchild.payload_start..grandchild.payload_end]);
+                    }
+                }
+            }
+            b"udta" => {
+                udta_strings.extend(extract_printable_strings(&payload[child.payload_start..child.payload_end]));
+            }
+            _ => {}
+        }
+    }
+
+    (dimensions, udta_strings)
+}
+
+/// Parses `data` (a whole file already read into memory) as an ISO-BMFF/
+/// QuickTime container, reading the `ftyp` brand list and `moov/trak/tkhd`
+/// dimensions plus `moov/udta` tag strings. Returns `None` if the bytes
+/// don't form a recognizable box at all (e.g. a real `.avi`, which is
+/// RIFF-based rather than box-based) — callers should fall back to their
+/// existing extension-only handling in that case. Takes the bytes rather
+/// than a path so a caller that already read the file (as
+/// `MetadataManager::analyze_file_with_options` does, for MD5/string
+/// scanning) doesn't have to read it again just to probe the container.
+pub fn probe_container(data: &[u8]) -> Option<VideoContainerInfo> {
+    let top_level = read_boxes(data);
+
+    if top_level.is_empty() {
+        return None;
+    }
+
+    let mut info = VideoContainerInfo::default();
+
+    for b in &top_level {
+        match &b.box_type {
+            b"ftyp" => {
+                let payload = &data[b.payload_start..b.payload_end];
+                if payload.len() >= 8 {
+                    info.major_brand = Some(String::from_utf8_lossy(&payload[0..4]).to_string());
+                    let mut brand_offset = 8;
+                    while brand_offset + 4 <= payload.len() {
+                        info.compatible_brands.push(String::from_utf8_lossy(&payload[brand_offset..brand_offset + 4]).to_string());
+                        brand_offset += 4;
+                    }
+                }
+            }
+            b"moov" => {
+                let (dimensions, udta_strings) = read_moov(&data[b.payload_start..b.payload_end]);
+                if let Some((w, h)) = dimensions {
+                    info.width = Some(w);
+                    info.height = Some(h);
+                }
+                info.udta_strings = udta_strings;
+            }
+            _ => {}
+        }
+    }
+
+    if info.major_brand.is_none() && info.width.is_none() && info.udta_strings.is_empty() {
+        return None;
+    }
+
+    Some(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a single ISO-BMFF box: 4-byte big-endian size, 4-byte type, payload.
+    fn make_box(box_type: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&((8 + payload.len()) as u32).to_be_bytes());
+        bytes.extend_from_slice(box_type);
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    /// Builds a `tkhd` payload encoding `width`/`height` as 16.16 fixed-point
+    /// values in its final 8 bytes, with arbitrary padding before them.
+    fn make_tkhd_payload(width: u32, height: u32) -> Vec<u8> {
+        let mut payload = vec![0u8; 76];
+        payload.extend_from_slice(&(width << 16).to_be_bytes());
+        payload.extend_from_slice(&(height << 16).to_be_bytes());
+        payload
+    }
+
+    fn make_ftyp_payload(major_brand: &[u8; 4], compatible_brands: &[&[u8; 4]]) -> Vec<u8> {
+        let mut payload = Vec::new();
+        payload.extend_from_slice(major_brand);
+        payload.extend_from_slice(&[0, 0, 0, 0]); // minor_version
+        for brand in compatible_brands {
+            payload.extend_from_slice(*brand);
+        }
+        payload
+    }
+
+    #[test]
+    fn probe_container_reads_ftyp_brands_and_tkhd_dimensions() {
+        let ftyp = make_box(b"ftyp", &make_ftyp_payload(b"isom", &[b"mp42", b"avc1"]));
+
+        let tkhd = make_box(b"tkhd", &make_tkhd_payload(1080, 1920));
+        let trak = make_box(b"trak", &tkhd);
+        let udta = make_box(b"udta", b"com.tiktok.app marker text here");
+        let moov_payload = [trak, udta].concat();
+        let moov = make_box(b"moov", &moov_payload);
+
+        let data = [ftyp, moov].concat();
+        let info = probe_container(&data).expect("well-formed container should parse");
+
+        assert_eq!(info.major_brand.as_deref(), Some("isom"));
+        assert_eq!(info.compatible_brands, vec!["mp42".to_string(), "avc1".to_string()]);
+        assert_eq!(info.width, Some(1080));
+        assert_eq!(info.height, Some(1920));
+        assert!(info.udta_strings.iter().any(|s| s.contains("tiktok")));
+    }
+
+    #[test]
+    fn probe_container_returns_none_for_non_box_data() {
+        let data = b"this is not a box-based container at all".to_vec();
+        assert!(probe_container(&data).is_none());
+    }
+
+    #[test]
+    fn probe_container_returns_none_for_empty_data() {
+        assert!(probe_container(&[]).is_none());
+    }
+
+    #[test]
+    fn read_boxes_stops_at_truncated_header_without_panicking() {
+        let ftyp = make_box(b"ftyp", &make_ftyp_payload(b"isom", &[]));
+        let mut data = ftyp.clone();
+        // Append a truncated header: claims more bytes than actually follow.
+        data.extend_from_slice(&100u32.to_be_bytes());
+        data.extend_from_slice(b"moov");
+        // No payload bytes at all follow, so this box is unreadable.
+
+        let boxes = read_boxes(&data);
+        assert_eq!(boxes.len(), 1);
+        assert_eq!(&boxes[0].box_type, b"ftyp");
+    }
+
+    #[test]
+    fn read_boxes_rejects_size_that_would_overflow_or_exceed_buffer() {
+        let mut data = Vec::new();
+        // size == 1 signals a 64-bit largesize follows; claim an enormous value.
+        data.extend_from_slice(&1u32.to_be_bytes());
+        data.extend_from_slice(b"moov");
+        data.extend_from_slice(&u64::MAX.to_be_bytes());
+
+        let boxes = read_boxes(&data);
+        assert!(boxes.is_empty());
+    }
+
+    #[test]
+    fn probe_container_handles_truncated_moov_gracefully() {
+        let ftyp = make_box(b"ftyp", &make_ftyp_payload(b"isom", &[]));
+        let mut moov = make_box(b"moov", &make_tkhd_payload(640, 480));
+        // Truncate the moov box so its declared size exceeds the actual buffer.
+        moov.truncate(moov.len() - 10);
+
+        let data = [ftyp, moov].concat();
+        let info = probe_container(&data).expect("ftyp alone is still a recognizable container");
+        assert_eq!(info.major_brand.as_deref(), Some("isom"));
+        assert!(info.width.is_none());
+    }
+
+    #[test]
+    fn extract_printable_strings_finds_runs_of_at_least_four_chars() {
+        let data = b"\x00\x00abc\x00defg\x01\x02hijkl\x00\x00";
+        let found = extract_printable_strings(data);
+        assert_eq!(found, vec!["defg".to_string(), "hijkl".to_string()]);
+    }
+}
@@ -0,0 +1,277 @@
+use std::fs;
+use std::path::Path;
+use anyhow::Result;
+use image::GenericImageView;
+use serde::{Deserialize, Serialize};
+
+/// Computes perceptual image hashes (dHash) for visual-similarity matching.
+///
+/// A dHash downscales an image to a small grid and encodes, per row, whether
+/// each pixel is brighter than its neighbour to the right. The result is a
+/// 64-bit fingerprint that survives re-encoding, resizing, and metadata
+/// stripping far better than a byte-exact hash like MD5.
+pub struct PerceptualHasher;
+
+impl PerceptualHasher {
+    /// Computes a 64-bit dHash for the image at `filepath`.
+    ///
+    /// The image is downscaled to 9x8 grayscale; each of the 8 rows emits 8
+    /// bits, one per adjacent-pixel brightness comparison, for 64 bits total.
+    pub fn dhash(filepath: &Path) -> Result<u64> {
+        let img = image::open(filepath)?;
+        Self::dhash_image(&img)
+    }
+
+    /// Computes a dHash directly from an already-decoded image.
+    pub fn dhash_image(img: &image::DynamicImage) -> Result<u64> {
+        let small = img.resize_exact(9, 8, image::imageops::FilterType::Triangle).to_luma8();
+
+        let mut hash: u64 = 0;
+        let mut bit = 0;
+        for y in 0..8 {
+            for x in 0..8 {
+                let left = small.get_pixel(x, y)[0];
+                let right = small.get_pixel(x + 1, y)[0];
+                if left > right {
+                    hash |= 1 << bit;
+                }
+                bit += 1;
+            }
+        }
+
+        Ok(hash)
+    }
+
+    /// Computes a dHash over a fractional sub-rectangle of `filepath`, for
+    /// matching chrome burned into a specific corner or strip (a watermark
+    /// logo or "@username" caption) rather than the whole frame.
+    /// `x_frac`/`y_frac`/`w_frac`/`h_frac` are in `[0.0, 1.0]`, relative to
+    /// image width/height. Returns `Ok(None)` instead of erroring when the
+    /// image is too small for the crop window to hold a meaningful 9x8 grid.
+    pub fn dhash_region(filepath: &Path, x_frac: f64, y_frac: f64, w_frac: f64, h_frac: f64) -> Result<Option<u64>> {
+        let img = image::open(filepath)?;
+        Self::dhash_region_image(&img, x_frac, y_frac, w_frac, h_frac)
+    }
+
+    /// Computes a region dHash directly from an already-decoded image.
+    pub fn dhash_region_image(img: &image::DynamicImage, x_frac: f64, y_frac: f64, w_frac: f64, h_frac: f64) -> Result<Option<u64>> {
+        let (width, height) = img.dimensions();
+        let crop_width = (width as f64 * w_frac).round() as u32;
+        let crop_height = (height as f64 * h_frac).round() as u32;
+
+        // A crop narrower than the 9x8 hash grid can't be downscaled into it
+        // meaningfully; skip instead of producing a degenerate hash.
+        if crop_width < 9 || crop_height < 8 {
+            return Ok(None);
+        }
+
+        let x = ((width as f64 * x_frac).round() as u32).min(width.saturating_sub(crop_width));
+        let y = ((height as f64 * y_frac).round() as u32).min(height.saturating_sub(crop_height));
+        let cropped = img.crop_imm(x, y, crop_width, crop_height);
+
+        Self::dhash_image(&cropped).map(Some)
+    }
+}
+
+/// Hamming distance between two 64-bit perceptual hashes (number of differing bits).
+pub fn hamming(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// A node in a BK-tree: a reference hash plus children keyed by their
+/// Hamming-distance edge from this node.
+struct BkNode {
+    hash: u64,
+    label: String,
+    children: Vec<(u32, BkNode)>,
+}
+
+/// BK-tree of reference perceptual hashes, supporting tolerance-bounded
+/// nearest-neighbour lookups in O(log n)-ish time instead of a linear scan.
+///
+/// Hamming distance satisfies the triangle inequality, which is what makes
+/// the BK-tree's pruning correct: a child is attached at the edge equal to
+/// its distance from its parent, and a query only needs to recurse into
+/// children whose edge falls within `[d - t, d + t]` of the query distance `d`.
+pub struct BkTree {
+    root: Option<BkNode>,
+}
+
+impl BkTree {
+    pub fn new() -> Self {
+        BkTree { root: None }
+    }
+
+    pub fn insert(&mut self, hash: u64, label: impl Into<String>) {
+        let label = label.into();
+        match &mut self.root {
+            None => {
+                self.root = Some(BkNode { hash, label, children: Vec::new() });
+            }
+            Some(root) => Self::insert_node(root, hash, label),
+        }
+    }
+
+    fn insert_node(node: &mut BkNode, hash: u64, label: String) {
+        let distance = hamming(node.hash, hash);
+        if let Some((_, child)) = node.children.iter_mut().find(|(d, _)| *d == distance) {
+            Self::insert_node(child, hash, label);
+        } else {
+            node.children.push((distance, BkNode { hash, label, children: Vec::new() }));
+        }
+    }
+
+    /// Returns the closest reference hash within `tolerance` bits, if any,
+    /// as `(label, distance)`.
+    pub fn find_within(&self, hash: u64, tolerance: u32) -> Option<(String, u32)> {
+        let mut best: Option<(String, u32)> = None;
+        if let Some(root) = &self.root {
+            Self::search_node(root, hash, tolerance, &mut best);
+        }
+        best
+    }
+
+    fn search_node(node: &BkNode, hash: u64, tolerance: u32, best: &mut Option<(String, u32)>) {
+        let distance = hamming(node.hash, hash);
+        if distance <= tolerance && best.as_ref().map(|(_, d)| distance < *d).unwrap_or(true) {
+            *best = Some((node.label.clone(), distance));
+        }
+
+        let low = distance.saturating_sub(tolerance);
+        let high = distance + tolerance;
+        for (edge, child) in &node.children {
+            if *edge >= low && *edge <= high {
+                Self::search_node(child, hash, tolerance, best);
+            }
+        }
+    }
+}
+
+impl Default for BkTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One user-supplied reference fingerprint, as loaded from a JSON hash
+/// file: a 64-bit dHash plus a human-readable label for what it matches
+/// (e.g. `"tiktok_logo_bottom_right"`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatermarkHashEntry {
+    pub hash: u64,
+    pub label: String,
+}
+
+/// Reference database of perceptual hashes for known TikTok-sourced media
+/// (confirmed exports, watermarked frames). Matching a scanned file's dHash
+/// against this tree catches TikTok content whose metadata has been
+/// stripped by re-saving or re-encoding.
+pub struct TikTokHashDatabase {
+    tree: BkTree,
+}
+
+impl TikTokHashDatabase {
+    /// Builds the database from a list of `(hash, label)` reference entries.
+    pub fn from_entries(entries: &[(u64, &str)]) -> Self {
+        let mut tree = BkTree::new();
+        for (hash, label) in entries {
+            tree.insert(*hash, *label);
+        }
+        TikTokHashDatabase { tree }
+    }
+
+    /// Checks a query hash against the database, accepting a normalized
+    /// tolerance in `[0.0, 1.0]` mapped onto the 0-64 bit Hamming range.
+    pub fn check(&self, hash: u64, normalized_tolerance: f64) -> Option<(String, u32)> {
+        let tolerance = (normalized_tolerance.clamp(0.0, 1.0) * 64.0).round() as u32;
+        self.tree.find_within(hash, tolerance)
+    }
+
+    /// Builds the database from bundled `entries`, then extends it with any
+    /// `WatermarkHashEntry` list found at `user_path`, so users can add
+    /// fingerprints for new watermark variants without a rebuild. Missing or
+    /// malformed user files are ignored - the bundled entries still load.
+    pub fn load(entries: &[(u64, &str)], user_path: Option<&Path>) -> Self {
+        let mut db = Self::from_entries(entries);
+
+        if let Some(path) = user_path {
+            if let Ok(content) = fs::read_to_string(path) {
+                if let Ok(user_entries) = serde_json::from_str::<Vec<WatermarkHashEntry>>(&content) {
+                    for entry in user_entries {
+                        db.tree.insert(entry.hash, entry.label);
+                    }
+                }
+            }
+        }
+
+        db
+    }
+}
+
+impl Default for TikTokHashDatabase {
+    /// An empty database with no known reference fingerprints; callers
+    /// populate it from a bundled or user-supplied hash list.
+    fn default() -> Self {
+        TikTokHashDatabase { tree: BkTree::new() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hamming_distance_counts_differing_bits() {
+        assert_eq!(hamming(0b0000, 0b0000), 0);
+        assert_eq!(hamming(0b0000, 0b0001), 1);
+        assert_eq!(hamming(0b1111, 0b0000), 4);
+        assert_eq!(hamming(u64::MAX, 0), 64);
+    }
+
+    #[test]
+    fn bk_tree_finds_exact_match_with_zero_tolerance() {
+        let mut tree = BkTree::new();
+        tree.insert(0b1010_1010, "a");
+        tree.insert(0b0000_0000, "b");
+
+        let result = tree.find_within(0b1010_1010, 0);
+        assert_eq!(result, Some(("a".to_string(), 0)));
+    }
+
+    #[test]
+    fn bk_tree_respects_tolerance_bound() {
+        let mut tree = BkTree::new();
+        tree.insert(0b0000_0000, "zero");
+
+        // Two bits differ; within tolerance should match, below it should not.
+        assert_eq!(tree.find_within(0b0000_0011, 2), Some(("zero".to_string(), 2)));
+        assert_eq!(tree.find_within(0b0000_0011, 1), None);
+    }
+
+    #[test]
+    fn bk_tree_returns_the_closest_of_multiple_candidates() {
+        let mut tree = BkTree::new();
+        tree.insert(0b0000_0000, "far");
+        tree.insert(0b0000_0001, "near");
+
+        // Query 1 bit differs from "near", 2 bits from "far".
+        let result = tree.find_within(0b0000_0011, 10);
+        assert_eq!(result, Some(("near".to_string(), 1)));
+    }
+
+    #[test]
+    fn hash_database_check_maps_normalized_tolerance_onto_hamming_range() {
+        let db = TikTokHashDatabase::from_entries(&[(0b0000_0000, "reference")]);
+
+        // 1 bit of 64 is well under a loose tolerance...
+        assert!(db.check(0b0000_0001, 0.5).is_some());
+        // ...but a zero tolerance should reject any non-exact match.
+        assert!(db.check(0b0000_0001, 0.0).is_none());
+    }
+
+    #[test]
+    fn default_hash_database_has_no_matches() {
+        let db = TikTokHashDatabase::default();
+        assert!(db.check(0, 1.0).is_none());
+    }
+}
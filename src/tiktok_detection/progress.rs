@@ -0,0 +1,18 @@
+/// Scan phases reported through a `ProgressData` update.
+pub const STAGE_CACHE_FILTERING: u8 = 0;
+pub const STAGE_PARALLEL_ANALYSIS: u8 = 1;
+pub const STAGE_FILE_ORGANIZATION: u8 = 2;
+
+/// A single progress update emitted by the scanner so a GUI or progress bar
+/// can render scan status without scraping stdout.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressData {
+    /// Current phase: 0 = cache filtering, 1 = parallel analysis, 2 = file organization.
+    pub current_stage: u8,
+    /// Highest stage number the scan will reach.
+    pub max_stage: u8,
+    /// Number of files processed so far in the current stage.
+    pub files_checked: usize,
+    /// Total number of files to process in the current stage.
+    pub files_to_check: usize,
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use crate::tiktok_detection::metadata_read::metadata_manager::FileMetadata;
+use crate::tiktok_detection::test_runner::TestResults;
+use crate::tiktok_detection::video_duplicate::VideoDuplicateDetector;
+
+/// Output format accepted by `ExportResults::export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Pretty-printed, multi-line JSON.
+    PrettyJson,
+    /// Single-line, compact JSON.
+    CompactJson,
+    /// Newline-delimited JSON: one compact `AnalysisRecord` per line, for
+    /// streaming into other tools (`jq`, log pipelines) without parsing one
+    /// giant array.
+    Ndjson,
+    /// One row per file, for spreadsheets and other CSV-reading tools.
+    Csv,
+}
+
+/// One analyzed file flattened into a row/object suitable for JSON or CSV
+/// export, independent of which detector (photo, video, test run) produced it.
+#[derive(Debug, Serialize)]
+pub struct AnalysisRecord {
+    pub filename: String,
+    pub filepath: String,
+    pub confidence_score: u32,
+    /// The same confidence band the phone organization guide buckets files
+    /// into: `"confirmed"` (70+), `"likely"` (40-69), `"possible"` (20-39),
+    /// or `"unlikely"`.
+    pub tier: String,
+    pub verdict: String,
+    pub is_tiktok: bool,
+    pub dimensions: Option<(u32, u32)>,
+    pub aspect_ratio: Option<f64>,
+    pub size_bytes: u64,
+    pub file_format: Option<String>,
+    /// Index into the duplicate-video groups found across the same batch of
+    /// files (see `VideoDuplicateDetector::find_duplicate_groups`), shared by
+    /// every file that's a visual duplicate of this one. `None` if this file
+    /// wasn't grouped with any other (not a video, unhashable, or unique).
+    pub duplicate_cluster_id: Option<usize>,
+    pub evidence_found: Vec<String>,
+    pub indicators: HashMap<String, String>,
+}
+
+/// Counts and total flagged size for a batch of `AnalysisRecord`s, broken
+/// down by confidence tier, so an export carries the same at-a-glance
+/// numbers the printed scan summary shows.
+#[derive(Debug, Default, Serialize)]
+pub struct ReportSummary {
+    pub counts_by_tier: HashMap<String, usize>,
+    /// Total size in bytes of every record outside the "unlikely" tier.
+    pub total_bytes_flagged: u64,
+}
+
+/// Builds a `ReportSummary` from `records`: per-tier counts plus the summed
+/// size of everything at "possible" confidence or above.
+pub fn summarize(records: &[AnalysisRecord]) -> ReportSummary {
+    let mut summary = ReportSummary::default();
+    for record in records {
+        *summary.counts_by_tier.entry(record.tier.clone()).or_insert(0) += 1;
+        if record.tier != "unlikely" {
+            summary.total_bytes_flagged += record.size_bytes;
+        }
+    }
+    summary
+}
+
+/// The confidence band `confidence_score` falls into, matching the tiers the
+/// phone organization guide buckets files into.
+pub(crate) fn tier_for(confidence_score: u32) -> &'static str {
+    match confidence_score {
+        70.. => "confirmed",
+        40..=69 => "likely",
+        20..=39 => "possible",
+        _ => "unlikely",
+    }
+}
+
+impl AnalysisRecord {
+    fn from_metadata(metadata: &FileMetadata, duplicate_cluster_id: Option<usize>) -> Self {
+        AnalysisRecord {
+            filename: metadata.filename.clone(),
+            filepath: metadata.filepath.clone(),
+            confidence_score: metadata.tiktok_analysis.confidence_score,
+            tier: tier_for(metadata.tiktok_analysis.confidence_score).to_string(),
+            verdict: metadata.tiktok_analysis.verdict.clone(),
+            is_tiktok: metadata.tiktok_analysis.is_tiktok,
+            dimensions: metadata.dimensions,
+            aspect_ratio: metadata.aspect_ratio,
+            size_bytes: metadata.size_bytes,
+            file_format: metadata.file_format.clone(),
+            duplicate_cluster_id,
+            evidence_found: metadata.tiktok_analysis.evidence_found.clone(),
+            indicators: metadata.tiktok_analysis.indicators.clone(),
+        }
+    }
+}
+
+/// Builds one `AnalysisRecord` per file in `files`, first grouping them into
+/// duplicate-video clusters so every record can carry its cluster id (or
+/// `None`) alongside the usual per-file fields.
+fn build_analysis_records(files: &[FileMetadata]) -> Vec<AnalysisRecord> {
+    let groups = VideoDuplicateDetector::new().find_duplicate_groups(files);
+    let mut cluster_by_filepath: HashMap<&str, usize> = HashMap::new();
+    for (cluster_id, group) in groups.iter().enumerate() {
+        if group.len() <= 1 {
+            continue;
+        }
+        for member in group {
+            cluster_by_filepath.insert(member.filepath.as_str(), cluster_id);
+        }
+    }
+
+    files.iter()
+        .map(|metadata| AnalysisRecord::from_metadata(metadata, cluster_by_filepath.get(metadata.filepath.as_str()).copied()))
+        .collect()
+}
+
+/// Implemented by anything that boils down to a set of analyzed files, so
+/// scan results and experiment runs can be serialized the same way instead
+/// of each building their own ad-hoc printed report.
+pub trait ExportResults {
+    /// Flattens `self` into one `AnalysisRecord` per analyzed file.
+    fn analysis_records(&self) -> Vec<AnalysisRecord>;
+
+    /// Serializes `self` in `format` and writes it to `output_path`, so
+    /// results can be piped into other tools or diffed across runs instead
+    /// of screen-scraping the printed summary.
+    fn export(&self, output_path: &Path, format: ExportFormat) -> Result<()> {
+        let records = self.analysis_records();
+
+        let content = match format {
+            ExportFormat::PrettyJson => serde_json::to_string_pretty(&records)
+                .context("Failed to serialize analysis records as pretty JSON")?,
+            ExportFormat::CompactJson => serde_json::to_string(&records)
+                .context("Failed to serialize analysis records as compact JSON")?,
+            ExportFormat::Ndjson => records_to_ndjson(&records)?,
+            ExportFormat::Csv => records_to_csv(&records),
+        };
+
+        fs::write(output_path, content)
+            .with_context(|| format!("Failed to write analysis report to {}", output_path.display()))?;
+
+        println!("📄 Analysis report written to: {}", output_path.display());
+        let summary = summarize(&records);
+        println!("   Tiers: {}", summary.counts_by_tier.iter()
+            .map(|(tier, count)| format!("{}={}", tier, count))
+            .collect::<Vec<_>>()
+            .join(", "));
+        println!("   Total bytes flagged (possible tier and above): {}", summary.total_bytes_flagged);
+        Ok(())
+    }
+}
+
+/// Renders `records` as newline-delimited JSON: one compact-JSON object per
+/// line, in record order, with a trailing newline after the last record.
+pub fn records_to_ndjson(records: &[AnalysisRecord]) -> Result<String> {
+    let mut ndjson = String::new();
+    for record in records {
+        ndjson.push_str(&serde_json::to_string(record).context("Failed to serialize analysis record as NDJSON")?);
+        ndjson.push('\n');
+    }
+    Ok(ndjson)
+}
+
+/// Renders `records` as a CSV document, one row per record, quoting fields
+/// per RFC 4180 where needed.
+pub fn records_to_csv(records: &[AnalysisRecord]) -> String {
+    let mut csv = String::from("filename,filepath,confidence_score,tier,verdict,is_tiktok,width,height,aspect_ratio,size_bytes,file_format,duplicate_cluster_id,evidence_found,indicators\n");
+
+    for record in records {
+        let (width, height) = record.dimensions.map_or((String::new(), String::new()), |(w, h)| (w.to_string(), h.to_string()));
+        let aspect_ratio = record.aspect_ratio.map_or(String::new(), |r| format!("{:.3}", r));
+        let file_format = record.file_format.clone().unwrap_or_default();
+        let duplicate_cluster_id = record.duplicate_cluster_id.map_or(String::new(), |id| id.to_string());
+        let evidence = record.evidence_found.join("; ");
+        let indicators = record.indicators.iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            csv_field(&record.filename),
+            csv_field(&record.filepath),
+            record.confidence_score,
+            csv_field(&record.tier),
+            csv_field(&record.verdict),
+            record.is_tiktok,
+            width,
+            height,
+            aspect_ratio,
+            record.size_bytes,
+            csv_field(&file_format),
+            duplicate_cluster_id,
+            csv_field(&evidence),
+            csv_field(&indicators),
+        ));
+    }
+
+    csv
+}
+
+/// Quotes a CSV field if it contains a comma, quote, or newline, doubling any
+/// embedded quotes per RFC 4180.
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl ExportResults for [FileMetadata] {
+    fn analysis_records(&self) -> Vec<AnalysisRecord> {
+        build_analysis_records(self)
+    }
+}
+
+impl ExportResults for TestResults {
+    fn analysis_records(&self) -> Vec<AnalysisRecord> {
+        build_analysis_records(&self.files)
+    }
+}
@@ -1,14 +1,42 @@
+use std::fs;
 use std::path::Path;
-use anyhow::Result;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use anyhow::{Context, Result};
+use crossbeam_channel::Sender;
 use rayon::prelude::*;
-use walkdir::WalkDir;
+use serde::Serialize;
 use crate::tiktok_detection::{
     tiktok_photo_det::TikTokPhotoDetector,
     tiktok_video_det::TikTokVideoDetector,
     metadata_read::metadata_manager::FileMetadata,
-    file_util::file_manager::FileManager,
+    file_util::file_manager::{DeleteMethod, Disposition, FileManager},
+    file_util::content_hash,
+    file_util::scan_filter::{ScanFilter, IMAGE_EXTENSIONS, VIDEO_EXTENSIONS},
+    progress::{ProgressData, STAGE_CACHE_FILTERING, STAGE_FILE_ORGANIZATION, STAGE_PARALLEL_ANALYSIS},
+    report_export::ExportFormat,
 };
 
+/// Minimum interval between progress updates sent during the parallel
+/// analysis stage, to avoid flooding the channel on fast local disks.
+const PROGRESS_THROTTLE: Duration = Duration::from_millis(100);
+
+/// Default floor below which a video is treated as a thumbnail or partial
+/// download rather than a genuine TikTok clip: real TikTok exports rarely
+/// land under this size even at the lowest bitrate presets.
+const DEFAULT_MIN_VIDEO_SIZE_BYTES: u64 = 50 * 1024;
+
+/// A media file discovered while walking the base path, together with the
+/// size and modified-time the walk already had to stat to find it. Carrying
+/// these forward means the cache lookup doesn't need to stat the file a
+/// second time, which matters on slow gvfs/mtp-mounted phone filesystems.
+struct MediaFileEntry {
+    path: std::path::PathBuf,
+    size: u64,
+    modified: String,
+}
+
 /// High-performance TikTok content scanner with multithreading support
 /// 
 /// This scanner uses parallel processing to analyze multiple files simultaneously,
@@ -21,10 +49,23 @@ pub struct TikTokScanner {
     video_detector: TikTokVideoDetector,
     /// File management system
     file_manager: FileManager,
+    /// Extension allow/deny list and excluded-directory patterns applied
+    /// during the folder walk, before any metadata analysis runs.
+    filter: ScanFilter,
+    /// Explicit worker-count override for the parallel analysis stage. `None`
+    /// means "pick a sane default" (see `effective_worker_count`).
+    worker_count: Option<usize>,
+    /// Extra machine-readable formats to write alongside the markdown phone
+    /// organization guide (empty by default). See `set_guide_export_formats`.
+    guide_export_formats: Vec<ExportFormat>,
+    /// Videos under this size are demoted out of the "possible" tier unless
+    /// other evidence (e.g. a matched MP4 metadata tag) confirms them
+    /// anyway. See `set_min_video_size_bytes`.
+    min_video_size_bytes: u64,
 }
 
 /// Results from a scanning operation
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct ScanResults {
     /// Total number of media files found
     pub total_files: usize,
@@ -40,22 +81,115 @@ pub struct ScanResults {
     pub skipped_cached: usize,
     /// Paths of files that were moved/organized
     pub moved_files: Vec<String>,
+    /// Full metadata for every file that was actually analyzed this run
+    /// (not skipped via cache), used to build an export via
+    /// `report_export::ExportResults`.
+    pub analyzed_files: Vec<FileMetadata>,
+    /// Paths plus reason for every file removed via `DeleteMethod::MoveToTrash`
+    /// or `DeleteMethod::Delete`.
+    pub deleted_files: Vec<(String, String)>,
+    /// Path plus reason for every discovered entry that wasn't a regular
+    /// file (a directory, a symlink that didn't resolve to one, or an
+    /// exotic node like a FIFO/socket/device) and so was skipped before
+    /// analysis instead of crashing the scan.
+    pub skipped_bad: Vec<(String, String)>,
+    /// Paths of zero-length or all-`0x00` files excluded from the guide
+    /// entirely (corrupt downloads, pre-allocated placeholders), never
+    /// analyzed or counted toward any confidence tier.
+    pub skipped_zeroed: Vec<String>,
+    /// Number of "possible"-tier videos demoted to "unlikely" for falling
+    /// under the configured minimum video size with no stronger signal to
+    /// confirm them. See `TikTokScanner::set_min_video_size_bytes`.
+    pub demoted_tiny_videos: usize,
 }
 
 impl TikTokScanner {
     pub fn new(scan_path: &Path) -> Result<Self> {
-        let photo_detector = TikTokPhotoDetector::new()?;
-        let video_detector = TikTokVideoDetector::new()?;
+        Self::new_with_filter(scan_path, ScanFilter::default())
+    }
+
+    /// Same as `new`, but applies `filter`'s extension allow/deny list and
+    /// excluded-directory patterns during the folder walk, so callers can
+    /// focus a scan (or skip slow subtrees like `Android/`) without waiting
+    /// for every file under `scan_path` to be analyzed.
+    pub fn new_with_filter(scan_path: &Path, filter: ScanFilter) -> Result<Self> {
+        Self::new_with_filter_and_ruleset(scan_path, filter, None)
+    }
+
+    /// Same as `new_with_filter`, but loads detection indicators and scoring
+    /// rules from a TOML or JSON ruleset file (see `rule_config::RuleSet`)
+    /// instead of the built-in ruleset when `ruleset_path` is given.
+    pub fn new_with_filter_and_ruleset(scan_path: &Path, filter: ScanFilter, ruleset_path: Option<&Path>) -> Result<Self> {
+        let (photo_detector, video_detector) = match ruleset_path {
+            Some(ruleset_path) => (
+                TikTokPhotoDetector::new_with_ruleset_file(ruleset_path)?,
+                TikTokVideoDetector::new_with_ruleset_file(ruleset_path)?,
+            ),
+            None => (TikTokPhotoDetector::new()?, TikTokVideoDetector::new()?),
+        };
         let file_manager = FileManager::new(scan_path)?;
 
         Ok(TikTokScanner {
             photo_detector,
             video_detector,
             file_manager,
+            filter,
+            worker_count: None,
+            guide_export_formats: Vec::new(),
+            min_video_size_bytes: DEFAULT_MIN_VIDEO_SIZE_BYTES,
         })
     }
 
+    /// Overrides the worker count used by the parallel analysis stage.
+    /// `None` restores the default of `min(num_cpus, 8)`. Ignored entirely
+    /// on detected MTP/phone paths, which always analyze with a single
+    /// worker regardless of this setting.
+    pub fn set_worker_count(&mut self, worker_count: Option<usize>) {
+        self.worker_count = worker_count;
+    }
+
+    /// Sets which machine-readable formats (JSON, CSV) get written alongside
+    /// the markdown phone organization guide, e.g. `[ExportFormat::Csv]` to
+    /// also get `tiktok_phone_organization_guide.csv`. Empty (the default)
+    /// writes only the markdown guide, unchanged.
+    pub fn set_guide_export_formats(&mut self, formats: Vec<ExportFormat>) {
+        self.guide_export_formats = formats;
+    }
+
+    /// Overrides the minimum video size (in bytes) a "possible"-tier
+    /// (20-39% confidence) video must meet to stay in that tier; smaller
+    /// videos are demoted to "unlikely" unless a stronger independent signal
+    /// (e.g. a matched MP4 metadata tag) already confirmed them. Defaults to
+    /// `DEFAULT_MIN_VIDEO_SIZE_BYTES`. Never affects images or files already
+    /// at 40%+ confidence.
+    pub fn set_min_video_size_bytes(&mut self, min_video_size_bytes: u64) {
+        self.min_video_size_bytes = min_video_size_bytes;
+    }
+
+    /// Picks how many threads the parallel analysis stage should use: the
+    /// configured override (or `min(num_cpus, 8)` by default), capped so we
+    /// never spin up more workers than there are files to analyze, and
+    /// forced down to a single worker on MTP/phone paths, where concurrent
+    /// reads over gvfs tend to be slower and more error-prone than
+    /// sequential ones (mirrors Mercurial's rayon worker-count cap, which
+    /// exists for the same oversubscription reason).
+    fn effective_worker_count(&self, files_to_analyze: usize) -> usize {
+        if self.file_manager.is_mtp_path() {
+            return 1;
+        }
+
+        let configured = self.worker_count.unwrap_or_else(|| num_cpus::get().min(8)).max(1);
+        configured.min(files_to_analyze.max(1))
+    }
+
     pub fn scan_folder(&mut self, move_files: bool) -> Result<ScanResults> {
+        self.scan_folder_with_delete(move_files, DeleteMethod::None, 0)
+    }
+
+    /// Same as `scan_folder`, but files at or above `delete_threshold`
+    /// confidence are disposed of via `delete_method` (trash or permanent
+    /// delete) instead of being moved/copied into confidence folders.
+    pub fn scan_folder_with_delete(&mut self, move_files: bool, delete_method: DeleteMethod, delete_threshold: u32) -> Result<ScanResults> {
         println!("Scanning folder: {}", self.file_manager.get_base_path().display());
         println!("TikTok detection folder: {}", self.file_manager.get_tiktok_folder().display());
 
@@ -72,6 +206,11 @@ impl TikTokScanner {
             unlikely_tiktok: 0,
             skipped_cached: 0,
             moved_files: Vec::new(),
+            analyzed_files: Vec::new(),
+            deleted_files: Vec::new(),
+            skipped_bad: Vec::new(),
+            skipped_zeroed: Vec::new(),
+            demoted_tiny_videos: 0,
         };
 
         // Get all media files from the base path (excluding tiktok_detection folder)
@@ -80,11 +219,19 @@ impl TikTokScanner {
 
         println!("Found {} media files to analyze\n", media_files.len());
 
-        for (i, file_path) in media_files.iter().enumerate() {
+        for (i, entry) in media_files.iter().enumerate() {
+            let file_path = entry.path.as_path();
             println!("[{}/{}] Analyzing: {}", i + 1, media_files.len(), file_path.file_name().unwrap().to_string_lossy());
 
-            // Check cache first
-            match self.file_manager.should_skip_file(file_path) {
+            if let Some(reason) = FileManager::bad_entry_reason(file_path) {
+                println!("  Skipped (not a regular file): {}", reason);
+                results.skipped_bad.push((file_path.to_string_lossy().to_string(), reason));
+                continue;
+            }
+
+            // Check cache first, using the size/modified time the walk already gathered,
+            // since a stat comparison is far cheaper than opening and reading the file.
+            match self.file_manager.should_skip_entry(file_path, entry.size, &entry.modified) {
                 Ok((should_skip, _cached_confidence)) => {
                     if should_skip {
                         println!("  Skipped (cached as non-TikTok)");
@@ -97,8 +244,17 @@ impl TikTokScanner {
                 }
             }
 
+            if self.is_zeroed_file(file_path) {
+                println!("  Skipped (zero-length or all-zero content)");
+                results.skipped_zeroed.push(file_path.to_string_lossy().to_string());
+                continue;
+            }
+
             // Analyze the file
-            let metadata = self.analyze_file(file_path)?;
+            let mut metadata = self.analyze_file(file_path)?;
+            if self.demote_if_tiny_video(file_path, &mut metadata) {
+                results.demoted_tiny_videos += 1;
+            }
             let confidence = metadata.tiktok_analysis.confidence_score;
 
             // Display result
@@ -120,10 +276,22 @@ impl TikTokScanner {
                 println!("     Evidence: {}", evidence_preview);
             }
 
+            results.analyzed_files.push(metadata.clone());
+
             // Handle file based on detection result
             if confidence >= 20 {
                 // TikTok detected (possible, likely, or confirmed)
-                if move_files {
+                if delete_method != DeleteMethod::None && confidence >= delete_threshold {
+                    match self.file_manager.dispose_file(file_path, delete_method, confidence) {
+                        Ok(Some(reason)) => {
+                            results.deleted_files.push((file_path.to_string_lossy().to_string(), reason));
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            eprintln!("     ERROR: Failed to dispose of file: {}", e);
+                        }
+                    }
+                } else if move_files {
                     match self.file_manager.move_file_to_tiktok_folder(file_path, confidence) {
                         Ok(new_path) => {
                             results.moved_files.push(new_path.to_string_lossy().to_string());
@@ -136,7 +304,7 @@ impl TikTokScanner {
                     // Just copy for preview mode
                     match self.file_manager.copy_file_to_tiktok_folder(file_path, confidence) {
                         Ok(_) => {
-                            println!("     INFO: Would move to: {}/", 
+                            println!("     INFO: Would move to: {}/",
                                      match confidence {
                                          70.. => "confirmed",
                                          40..=69 => "likely",
@@ -154,7 +322,7 @@ impl TikTokScanner {
                     eprintln!("     WARNING: Failed to cache file: {}", e);
                 }
             }
-            
+
             println!();
         }
 
@@ -167,22 +335,43 @@ impl TikTokScanner {
 
     /// Performs parallel scanning of media files for TikTok detection using multiple threads.
     /// This method processes files concurrently for analysis, then handles file operations
-    /// sequentially to avoid race conditions with file system operations.
-    /// 
+    /// sequentially to avoid race conditions with file system operations. Thin wrapper over
+    /// `scan_folder_parallel_with_options` for callers that don't need progress reporting,
+    /// cancellation, or delete disposal.
+    ///
     /// # Arguments
-    /// * `move_files` - If true, moves detected TikTok files to organized folders. If false, 
+    /// * `move_files` - If true, moves detected TikTok files to organized folders. If false,
     ///                  only performs preview mode without actually moving files.
-    /// 
+    ///
     /// # Returns
     /// Returns ScanResults containing statistics about the scan operation.
     pub fn scan_folder_parallel(&mut self, move_files: bool) -> Result<ScanResults> {
+        self.scan_folder_parallel_with_options(move_files, DeleteMethod::None, 0, None, None)
+    }
+
+    /// Same as `scan_folder_parallel`, but with every optional extension in
+    /// one place: files at or above `delete_threshold` confidence are
+    /// disposed of via `delete_method` (trash or permanent delete) instead of
+    /// being moved/copied into confidence folders; progress is reported
+    /// through `progress` (throttled to roughly every 100ms) if given; and
+    /// the scan can be interrupted early via `stop_flag`. Worker threads
+    /// check the flag between files during the parallel analysis stage; if
+    /// it's set, a partial `ScanResults` is returned covering whatever was
+    /// analyzed before the stop was observed.
+    pub fn scan_folder_parallel_with_options(
+        &mut self,
+        move_files: bool,
+        delete_method: DeleteMethod,
+        delete_threshold: u32,
+        progress: Option<Sender<ProgressData>>,
+        stop_flag: Option<Arc<AtomicBool>>,
+    ) -> Result<ScanResults> {
         println!("Scanning folder (parallel): {}", self.file_manager.get_base_path().display());
         println!("TikTok detection folder: {}", self.file_manager.get_tiktok_folder().display());
 
         // Get all media files
         let media_files = self.get_media_files()?;
-        println!("Found {} media files to analyze (using {} threads)\n", 
-                 media_files.len(), num_cpus::get());
+        println!("Found {} media files to analyze\n", media_files.len());
 
         let mut results = ScanResults {
             total_files: media_files.len(),
@@ -192,47 +381,132 @@ impl TikTokScanner {
             unlikely_tiktok: 0,
             skipped_cached: 0,
             moved_files: Vec::new(),
+            analyzed_files: Vec::new(),
+            deleted_files: Vec::new(),
+            skipped_bad: Vec::new(),
+            skipped_zeroed: Vec::new(),
+            demoted_tiny_videos: 0,
         };
 
-        // First, check cache and collect files that need analysis
+        // First, check cache and collect files that need analysis. This uses
+        // the size/modified time already gathered by `get_media_files`, so
+        // known non-TikTok files are eliminated without a second stat before
+        // any (much more expensive) file content is ever read.
         let mut files_to_analyze = Vec::new();
-        for (i, file_path) in media_files.iter().enumerate() {
-            println!("[{}/{}] Checking cache: {}", i + 1, media_files.len(), 
+        for (i, entry) in media_files.iter().enumerate() {
+            let file_path = entry.path.as_path();
+            println!("[{}/{}] Checking cache: {}", i + 1, media_files.len(),
                      file_path.file_name().unwrap().to_string_lossy());
 
-            match self.file_manager.should_skip_file(file_path) {
+            if let Some(sender) = &progress {
+                let _ = sender.try_send(ProgressData {
+                    current_stage: STAGE_CACHE_FILTERING,
+                    max_stage: STAGE_FILE_ORGANIZATION,
+                    files_checked: i + 1,
+                    files_to_check: media_files.len(),
+                });
+            }
+
+            if let Some(reason) = FileManager::bad_entry_reason(file_path) {
+                println!("  Skipped (not a regular file): {}", reason);
+                results.skipped_bad.push((file_path.to_string_lossy().to_string(), reason));
+                continue;
+            }
+
+            match self.file_manager.should_skip_entry(file_path, entry.size, &entry.modified) {
                 Ok((should_skip, _cached_confidence)) => {
                     if should_skip {
                         println!("  Skipped (cached as non-TikTok)");
                         results.skipped_cached += 1;
-                    } else {
-                        files_to_analyze.push(file_path);
+                        continue;
                     }
                 },
                 Err(_) => {
-                    files_to_analyze.push(file_path);
+                    // Continue with analysis if cache check fails
                 }
             }
+
+            if self.is_zeroed_file(file_path) {
+                println!("  Skipped (zero-length or all-zero content)");
+                results.skipped_zeroed.push(file_path.to_string_lossy().to_string());
+                continue;
+            }
+
+            files_to_analyze.push(file_path);
         }
 
-        println!("\nAnalyzing {} files in parallel...\n", files_to_analyze.len());
+        let worker_count = self.effective_worker_count(files_to_analyze.len());
+        println!("\nAnalyzing {} files in parallel (using {} worker{})...\n",
+                 files_to_analyze.len(), worker_count, if worker_count == 1 { "" } else { "s" });
+
+        // Cap the rayon pool at `worker_count` rather than using the global
+        // pool directly: the global pool defaults to one thread per core,
+        // which oversubscribes small jobs and does the wrong thing entirely
+        // on MTP/phone paths, where we want exactly one worker. Building a
+        // scoped pool here keeps that decision local to this call instead of
+        // reconfiguring rayon's global pool for the whole process.
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(worker_count)
+            .build()
+            .context("Failed to build analysis thread pool")?;
+
+        // Perform parallel analysis on files that need it, bailing out early
+        // if `stop_flag` is raised and reporting throttled progress updates.
+        // Only the read-only analysis runs inside the pool; results are
+        // funneled back here and `NotTikTokCache` is only ever mutated by the
+        // single-threaded processing loop below, so the cache itself never
+        // needs its own locking.
+        let files_checked = AtomicUsize::new(0);
+        let demoted_tiny_videos = AtomicUsize::new(0);
+        let last_progress_sent = std::sync::Mutex::new(Instant::now() - PROGRESS_THROTTLE);
+        let analysis_results: Vec<_> = pool.install(|| {
+            files_to_analyze
+                .par_iter()
+                .enumerate()
+                .map(|(i, file_path)| {
+                    if let Some(flag) = &stop_flag {
+                        if flag.load(Ordering::Relaxed) {
+                            return (file_path, None);
+                        }
+                    }
+
+                    println!("[{}/{}] Analyzing: {}", i + 1, files_to_analyze.len(),
+                             file_path.file_name().unwrap().to_string_lossy());
 
-        // Perform parallel analysis on files that need it
-        let analysis_results: Vec<_> = files_to_analyze
-            .par_iter()
-            .enumerate()
-            .map(|(i, file_path)| {
-                println!("[{}/{}] Analyzing: {}", i + 1, files_to_analyze.len(), 
-                         file_path.file_name().unwrap().to_string_lossy());
+                    let metadata_result = self.analyze_file(file_path).map(|mut metadata| {
+                        if self.demote_if_tiny_video(file_path, &mut metadata) {
+                            demoted_tiny_videos.fetch_add(1, Ordering::Relaxed);
+                        }
+                        metadata
+                    });
+                    let checked = files_checked.fetch_add(1, Ordering::Relaxed) + 1;
+
+                    if let Some(sender) = &progress {
+                        let mut last_sent = last_progress_sent.lock().unwrap();
+                        if last_sent.elapsed() >= PROGRESS_THROTTLE {
+                            let _ = sender.try_send(ProgressData {
+                                current_stage: STAGE_PARALLEL_ANALYSIS,
+                                max_stage: STAGE_FILE_ORGANIZATION,
+                                files_checked: checked,
+                                files_to_check: files_to_analyze.len(),
+                            });
+                            *last_sent = Instant::now();
+                        }
+                    }
 
-                let metadata_result = self.analyze_file(file_path);
-                (file_path, metadata_result)
-            })
-            .collect();
+                    (file_path, Some(metadata_result))
+                })
+                .collect()
+        });
+        results.demoted_tiny_videos = demoted_tiny_videos.load(Ordering::Relaxed);
 
         // Process results sequentially to handle file operations safely
         println!("\nProcessing results and organizing files...\n");
         for (file_path, metadata_result) in analysis_results {
+            let metadata_result = match metadata_result {
+                Some(result) => result,
+                None => continue, // skipped because the stop flag was raised
+            };
             match metadata_result {
                 Ok(metadata) => {
                     let confidence = metadata.tiktok_analysis.confidence_score;
@@ -258,10 +532,22 @@ impl TikTokScanner {
                         println!("     Evidence: {}", evidence_preview);
                     }
 
+                    results.analyzed_files.push(metadata.clone());
+
                     // Handle file based on detection result
                     if confidence >= 20 {
                         // TikTok detected (possible, likely, or confirmed)
-                        if move_files {
+                        if delete_method != DeleteMethod::None && confidence >= delete_threshold {
+                            match self.file_manager.dispose_file(file_path, delete_method, confidence) {
+                                Ok(Some(reason)) => {
+                                    results.deleted_files.push((file_path.to_string_lossy().to_string(), reason));
+                                }
+                                Ok(None) => {}
+                                Err(e) => {
+                                    eprintln!("     ERROR: Failed to dispose of file: {}", e);
+                                }
+                            }
+                        } else if move_files {
                             match self.file_manager.move_file_to_tiktok_folder(file_path, confidence) {
                                 Ok(new_path) => {
                                     results.moved_files.push(new_path.to_string_lossy().to_string());
@@ -306,15 +592,11 @@ impl TikTokScanner {
         // Generate phone organization guide if MTP filesystem detected and TikTok files found
         let total_detected = results.confirmed_tiktok + results.likely_tiktok + results.possible_tiktok;
         if total_detected > 0 {
-            let base_path_str = self.file_manager.get_base_path().to_string_lossy();
-            let is_phone_filesystem = base_path_str.contains("gvfs/mtp") || base_path_str.contains("run/user");
-            
-            if is_phone_filesystem {
-                // Collect detected files for the guide
-                let detected_files = Vec::new();
-                // Note: In a real implementation, we'd track the file paths and confidence scores
-                // For now, we'll create the guide without specific file details
-                if let Err(e) = self.file_manager.create_phone_organization_guide(&detected_files) {
+            if self.file_manager.is_mtp_path() {
+                // `analyzed_files` already carries every file's confidence score and
+                // path, so the guide can bucket by confidence and find duplicate
+                // clips without re-scanning anything.
+                if let Err(e) = self.file_manager.create_phone_organization_guide_with_exports(&results.analyzed_files, &self.guide_export_formats, results.skipped_zeroed.len(), results.demoted_tiny_videos) {
                     eprintln!("WARNING: Could not create organization guide: {}", e);
                 } else {
                     println!("\n[PHONE] Phone filesystem detected - organization guide created!");
@@ -327,38 +609,171 @@ impl TikTokScanner {
         Ok(results)
     }
 
-    fn get_media_files(&self) -> Result<Vec<std::path::PathBuf>> {
-        let mut media_files = Vec::new();
+    /// Moves every file in `analyzed_files` at or above `confidence_threshold`
+    /// into this run's quarantine directory via `FileManager::dispose_with`,
+    /// recording each move in that directory's `manifest.json` so it can be
+    /// undone later with `restore_from_manifest`. Returns how many files were
+    /// quarantined.
+    pub fn quarantine_detected(&mut self, analyzed_files: &[FileMetadata], confidence_threshold: u32) -> Result<usize> {
+        let mut quarantined = 0;
+        for metadata in analyzed_files {
+            let confidence = metadata.tiktok_analysis.confidence_score;
+            if confidence < confidence_threshold {
+                continue;
+            }
+            let file_path = Path::new(&metadata.filepath);
+            self.file_manager.dispose_with(file_path, Disposition::Quarantine, confidence)?;
+            quarantined += 1;
+        }
+        Ok(quarantined)
+    }
+
+    /// Reverses a previous `quarantine_detected` run by replaying
+    /// `manifest_path` (as written by `FileManager::quarantine_file`),
+    /// moving every quarantined file back to its original location.
+    pub fn restore_from_manifest(&self, manifest_path: &Path) -> Result<Vec<String>> {
+        self.file_manager.restore_from_manifest(manifest_path)
+    }
+
+    /// Collects every media file under the base path along with the cheap
+    /// stat info (size, modified time) the walk already gathers to find it.
+    ///
+    /// The top-level entries are walked in parallel with rayon rather than
+    /// a single sequential walk over the whole tree: on gvfs/mtp phone
+    /// filesystems each directory listing and stat is a slow round-trip, so
+    /// spreading subtrees across threads is where the real win is, well
+    /// before any file content is ever read. Directory listings are served
+    /// from `FileManager`'s FS-schema cache, so a repeat scan of a subtree
+    /// that hasn't changed since the last run skips re-reading it entirely
+    /// instead of re-walking and re-stating every entry.
+    fn get_media_files(&self) -> Result<Vec<MediaFileEntry>> {
         let base_path = self.file_manager.get_base_path();
         let tiktok_folder = self.file_manager.get_tiktok_folder();
 
-        for entry in walkdir::WalkDir::new(base_path) {
-            let entry = entry?;
-            let path = entry.path();
+        let top_level: Vec<std::path::PathBuf> = fs::read_dir(base_path)
+            .with_context(|| format!("Failed to read directory {}", base_path.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect();
+
+        let media_files: Vec<MediaFileEntry> = top_level
+            .par_iter()
+            .flat_map(|top_entry| {
+                let mut found = Vec::new();
+
+                if top_entry.is_dir() {
+                    self.collect_media_recursive(top_entry, tiktok_folder, &mut found);
+                } else if let Some(entry) = self.media_entry_for_file(top_entry) {
+                    found.push(entry);
+                }
+
+                found
+            })
+            .collect();
+
+        // Persist whatever directory listings were read or reused this run,
+        // so the next scan of an unchanged tree can skip straight to the
+        // cached entries instead of walking again.
+        let _ = self.file_manager.save_fs_schema_cache();
+
+        Ok(media_files)
+    }
+
+    /// Recursively collects media files under `dir` into `found`, pruning
+    /// excluded directories (and the `tiktok_detection` organization folder
+    /// itself) before ever listing them, and serving each directory's
+    /// listing from the FS-schema cache instead of a fresh `read_dir`.
+    fn collect_media_recursive(&self, dir: &Path, tiktok_folder: &Path, found: &mut Vec<MediaFileEntry>) {
+        if dir.starts_with(tiktok_folder) || self.filter.excluded_dirs.excludes(dir) {
+            return;
+        }
 
-            // Skip the tiktok_detection folder to avoid processing already moved files
-            if path.starts_with(tiktok_folder) {
+        let entries = match self.file_manager.list_dir_cached(dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        for entry in entries {
+            let path = dir.join(&entry.name);
+
+            if entry.is_dir {
+                self.collect_media_recursive(&path, tiktok_folder, found);
                 continue;
             }
 
-            if path.is_file() {
-                if let Some(ext) = path.extension() {
-                    let ext_str = ext.to_str().unwrap_or("").to_lowercase();
-                    if ["jpg", "jpeg", "png", "webp", "gif", "bmp", "mp4", "mov", "avi", "mkv", "flv", "webm"]
-                        .contains(&ext_str.as_str()) {
-                        media_files.push(path.to_path_buf());
-                    }
-                }
+            let ext_str = match path.extension().and_then(|e| e.to_str()) {
+                Some(ext) => ext.to_lowercase(),
+                None => continue,
+            };
+            if !IMAGE_EXTENSIONS.contains(&ext_str.as_str()) && !VIDEO_EXTENSIONS.contains(&ext_str.as_str()) {
+                continue;
             }
+            if !self.filter.extensions.is_allowed(&path) {
+                continue;
+            }
+
+            found.push(MediaFileEntry { path, size: entry.size, modified: entry.modified });
         }
+    }
 
-        Ok(media_files)
+    /// Builds a `MediaFileEntry` for a top-level path that turned out to be
+    /// a plain file rather than a directory (so there's no schema to cache),
+    /// or `None` if it isn't a recognized, allowed media extension.
+    fn media_entry_for_file(&self, path: &Path) -> Option<MediaFileEntry> {
+        let ext_str = path.extension().and_then(|e| e.to_str())?.to_lowercase();
+        if !IMAGE_EXTENSIONS.contains(&ext_str.as_str()) && !VIDEO_EXTENSIONS.contains(&ext_str.as_str()) {
+            return None;
+        }
+        if !self.filter.extensions.is_allowed(path) {
+            return None;
+        }
+
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?
+            .duration_since(std::time::UNIX_EPOCH).ok()?;
+        let modified_str = chrono::DateTime::<chrono::Utc>::from(std::time::UNIX_EPOCH + modified).to_rfc3339();
+
+        Some(MediaFileEntry { path: path.to_path_buf(), size: metadata.len(), modified: modified_str })
+    }
+
+    /// Whether `file_path` is zero-length or all-`0x00` content, cheap
+    /// enough to run on every file before the (much more expensive) real
+    /// analysis. Treats a failed read as "not zeroed" rather than erroring
+    /// the whole scan over one unreadable file.
+    fn is_zeroed_file(&self, file_path: &Path) -> bool {
+        content_hash::is_zeroed_file(file_path).unwrap_or(false)
+    }
+
+    /// Demotes a "possible"-tier (20-39%) video out of that tier to 19%
+    /// (the top of "unlikely", just under "possible") when it's smaller than `min_video_size_bytes`
+    /// and nothing stronger already confirmed it (a matched MP4 metadata
+    /// tag already pushes confidence to 95+, well above this range, so its
+    /// presence alone is enough to skip the demotion). Returns whether a
+    /// demotion happened, so the caller can re-categorize and report it.
+    fn demote_if_tiny_video(&self, file_path: &Path, metadata: &mut FileMetadata) -> bool {
+        let ext_str = file_path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+        if !VIDEO_EXTENSIONS.contains(&ext_str.as_str()) {
+            return false;
+        }
+
+        let confidence = metadata.tiktok_analysis.confidence_score;
+        if !(20..=39).contains(&confidence) || metadata.size_bytes >= self.min_video_size_bytes {
+            return false;
+        }
+
+        metadata.tiktok_analysis.confidence_score = 19;
+        metadata.tiktok_analysis.is_tiktok = false;
+        metadata.tiktok_analysis.evidence_found.push(format!(
+            "Demoted from possible: {} bytes is under the {}-byte minimum video size",
+            metadata.size_bytes, self.min_video_size_bytes
+        ));
+        metadata.tiktok_analysis.verdict = "UNLIKELY: Demoted for falling under the minimum video size".to_string();
+        true
     }
 
     fn analyze_file(&self, file_path: &Path) -> Result<FileMetadata> {
         if let Some(ext) = file_path.extension() {
             let ext_str = ext.to_str().unwrap_or("").to_lowercase();
-            if ["mp4", "mov", "avi", "mkv", "flv", "webm"].contains(&ext_str.as_str()) {
+            if VIDEO_EXTENSIONS.contains(&ext_str.as_str()) {
                 self.video_detector.analyze_video(file_path)
             } else {
                 self.photo_detector.analyze_image(file_path)
@@ -377,6 +792,18 @@ impl TikTokScanner {
         if results.skipped_cached > 0 {
             println!("Files skipped (cached): {}", results.skipped_cached);
         }
+        if !results.skipped_bad.is_empty() {
+            println!("Files skipped (not regular files): {}", results.skipped_bad.len());
+            for (path, reason) in &results.skipped_bad {
+                println!("   - {}: {}", path, reason);
+            }
+        }
+        if !results.skipped_zeroed.is_empty() {
+            println!("Files skipped (zero-length or all-zero content): {}", results.skipped_zeroed.len());
+        }
+        if results.demoted_tiny_videos > 0 {
+            println!("Possible-tier videos demoted (under minimum video size): {}", results.demoted_tiny_videos);
+        }
         println!();
         println!("[CONFIRMED] TikTok: {}", results.confirmed_tiktok);
         println!("[LIKELY] TikTok: {}", results.likely_tiktok);
@@ -1,11 +1,24 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use anyhow::Result;
+use crossbeam_channel::Sender;
+use rayon::prelude::*;
+use walkdir::WalkDir;
 use crate::tiktok_detection::{
     tiktok_photo_det::TikTokPhotoDetector,
     tiktok_video_det::TikTokVideoDetector,
     metadata_read::metadata_manager::FileMetadata,
+    progress::{ProgressData, STAGE_PARALLEL_ANALYSIS},
 };
 
+/// Extensions a test-set file walk will analyze, split by which detector
+/// handles them. The image list matches `TikTokPhotoDetector::analyze_folder`;
+/// the video list covers only mp4/mov/avi, not the mkv/flv/webm that
+/// `scan_filter::VIDEO_EXTENSIONS` also includes.
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "gif", "bmp"];
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "avi"];
+
 pub struct TestRunner {
     photo_detector: TikTokPhotoDetector,
     video_detector: TikTokVideoDetector,
@@ -31,15 +44,28 @@ impl TestRunner {
     }
 
     pub fn run_experiment(&self, tiktok_folder: &Path, not_tiktok_folder: &Path) -> Result<()> {
+        self.run_experiment_with_progress(tiktok_folder, not_tiktok_folder, None, None)
+    }
+
+    /// Same as `run_experiment`, but additionally reports progress through
+    /// `progress` and can be interrupted early via `stop_flag`, exactly like
+    /// `TikTokScanner::scan_folder_parallel_with_options`.
+    pub fn run_experiment_with_progress(
+        &self,
+        tiktok_folder: &Path,
+        not_tiktok_folder: &Path,
+        progress: Option<Sender<ProgressData>>,
+        stop_flag: Option<Arc<AtomicBool>>,
+    ) -> Result<()> {
         println!("🧪 TIKTOK DETECTION EXPERIMENT");
         println!("{}", "=".repeat(80));
         println!("Testing Rust TikTok detection algorithm on test datasets...\n");
 
         // Analyze TikTok folder
-        let tiktok_results = self.analyze_test_folder(tiktok_folder, "TikTok Test Set")?;
-        
-        // Analyze not-TikTok folder  
-        let not_tiktok_results = self.analyze_test_folder(not_tiktok_folder, "Not-TikTok Test Set")?;
+        let tiktok_results = self.analyze_test_folder(tiktok_folder, "TikTok Test Set", progress.clone(), stop_flag.clone())?;
+
+        // Analyze not-TikTok folder
+        let not_tiktok_results = self.analyze_test_folder(not_tiktok_folder, "Not-TikTok Test Set", progress, stop_flag)?;
 
         // Generate comprehensive report
         self.generate_experiment_report(&tiktok_results, &not_tiktok_results);
@@ -47,7 +73,21 @@ impl TestRunner {
         Ok(())
     }
 
-    fn analyze_test_folder(&self, folder_path: &Path, folder_name: &str) -> Result<TestResults> {
+    /// Walks `folder_path` once for every analyzable image/video, then
+    /// analyzes them with a rayon parallel iterator so large test sets
+    /// don't block on one file at a time. `stop_flag`, if set, is checked by
+    /// each worker so a scan can be cancelled early; `progress`, if set,
+    /// receives throttled-free per-file updates (test runs are one-shot, not
+    /// a background scan, so there's no need to rate-limit them). Results
+    /// are sorted by filepath afterward so the report is deterministic
+    /// regardless of which worker finished first.
+    fn analyze_test_folder(
+        &self,
+        folder_path: &Path,
+        folder_name: &str,
+        progress: Option<Sender<ProgressData>>,
+        stop_flag: Option<Arc<AtomicBool>>,
+    ) -> Result<TestResults> {
         println!("\n🔍 ANALYZING FOLDER: {}", folder_name);
         println!("{}", "=".repeat(80));
 
@@ -55,39 +95,60 @@ impl TestRunner {
             return Err(anyhow::anyhow!("Folder not found: {}", folder_path.display()));
         }
 
-        let mut all_files = Vec::new();
-        
-        // Analyze images
-        match self.photo_detector.analyze_folder(folder_path) {
-            Ok(mut image_files) => {
-                println!("📸 Found {} image files", image_files.len());
-                all_files.append(&mut image_files);
-            }
-            Err(e) => eprintln!("Error analyzing images: {}", e),
-        }
+        let media_files = self.collect_media_files(folder_path);
+        println!("📁 Found {} media files (using {} threads)\n", media_files.len(), num_cpus::get());
+
+        let files_checked = std::sync::atomic::AtomicUsize::new(0);
+        let mut all_files: Vec<FileMetadata> = media_files
+            .par_iter()
+            .filter_map(|path| {
+                if let Some(flag) = &stop_flag {
+                    if flag.load(Ordering::Relaxed) {
+                        return None;
+                    }
+                }
 
-        // Analyze videos
-        match self.video_detector.analyze_folder(folder_path) {
-            Ok(mut video_files) => {
-                println!("🎥 Found {} video files", video_files.len());
-                all_files.append(&mut video_files);
-            }
-            Err(e) => eprintln!("Error analyzing videos: {}", e),
-        }
+                let result = if is_video(path) {
+                    self.video_detector.analyze_video(path)
+                } else {
+                    self.photo_detector.analyze_image(path)
+                };
+
+                let checked = files_checked.fetch_add(1, Ordering::Relaxed) + 1;
+                if let Some(sender) = &progress {
+                    let _ = sender.try_send(ProgressData {
+                        current_stage: STAGE_PARALLEL_ANALYSIS,
+                        max_stage: STAGE_PARALLEL_ANALYSIS,
+                        files_checked: checked,
+                        files_to_check: media_files.len(),
+                    });
+                }
+
+                match result {
+                    Ok(metadata) => Some(metadata),
+                    Err(e) => {
+                        eprintln!("Error analyzing {}: {}", path.display(), e);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        all_files.sort_by(|a, b| a.filepath.cmp(&b.filepath));
 
         let total_files = all_files.len();
         println!("📁 Total media files: {}\n", total_files);
 
         // Categorize results
         let mut confirmed_tiktok = 0;
-        let mut likely_tiktok = 0; 
+        let mut likely_tiktok = 0;
         let mut possible_tiktok = 0;
         let mut unlikely_tiktok = 0;
 
         // Process each file and show results
         for (i, file) in all_files.iter().enumerate() {
             println!("[{}/{}] 📄 {}", i + 1, total_files, file.filename);
-            
+
             let confidence_icon = match file.tiktok_analysis.confidence_score {
                 70.. => { confirmed_tiktok += 1; "🔴" },
                 40..=69 => { likely_tiktok += 1; "🟡" },
@@ -96,7 +157,7 @@ impl TestRunner {
             };
 
             println!("  {} {} (Confidence: {}/100)", confidence_icon, file.tiktok_analysis.verdict, file.tiktok_analysis.confidence_score);
-            
+
             if !file.tiktok_analysis.evidence_found.is_empty() {
                 let evidence_preview = if file.tiktok_analysis.evidence_found.len() > 2 {
                     format!("{}, {}...", file.tiktok_analysis.evidence_found[0], file.tiktok_analysis.evidence_found[1])
@@ -131,6 +192,27 @@ impl TestRunner {
         })
     }
 
+    /// Walks `folder_path` for every file with a recognized image or video
+    /// extension, in whatever order `WalkDir` yields them (the caller sorts
+    /// the analyzed results afterward, so walk order doesn't matter here).
+    fn collect_media_files(&self, folder_path: &Path) -> Vec<PathBuf> {
+        WalkDir::new(folder_path)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.into_path())
+            .filter(|path| {
+                path.is_file()
+                    && path.extension()
+                        .and_then(|ext| ext.to_str())
+                        .map(|ext| {
+                            let ext = ext.to_lowercase();
+                            IMAGE_EXTENSIONS.contains(&ext.as_str()) || VIDEO_EXTENSIONS.contains(&ext.as_str())
+                        })
+                        .unwrap_or(false)
+            })
+            .collect()
+    }
+
     fn generate_experiment_report(&self, tiktok_results: &TestResults, not_tiktok_results: &TestResults) {
         println!("\n🎯 EXPERIMENT RESULTS SUMMARY");
         println!("{}", "=".repeat(80));
@@ -241,3 +323,12 @@ impl Default for TestRunner {
         Self::new().expect("Failed to create TestRunner")
     }
 }
+
+/// Whether `path`'s extension identifies it as a video rather than an image,
+/// so `analyze_test_folder` can dispatch to the matching detector.
+fn is_video(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| VIDEO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
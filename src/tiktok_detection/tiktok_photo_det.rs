@@ -1,27 +1,164 @@
 use std::path::Path;
 use anyhow::Result;
 use crate::tiktok_detection::metadata_read::metadata_manager::{MetadataManager, FileMetadata};
+use crate::tiktok_detection::perceptual_hash::{PerceptualHasher, TikTokHashDatabase};
+
+/// Normalized tolerance (0.0-1.0, mapped onto the 0-64 bit Hamming range)
+/// used when matching a scanned image's dHash against known TikTok assets.
+const PERCEPTUAL_MATCH_TOLERANCE: f64 = 0.06;
+
+/// Confidence bump awarded when a file's perceptual hash matches a known
+/// TikTok-sourced asset within tolerance.
+const PERCEPTUAL_MATCH_SCORE: u32 = 45;
+
+/// Normalized tolerance used when matching a cropped watermark/caption
+/// region's dHash against `WATERMARK_REGIONS`' reference database. Looser
+/// than `PERCEPTUAL_MATCH_TOLERANCE` since a small crop is more sensitive to
+/// exact logo placement and re-encoding artifacts.
+const REGION_MATCH_TOLERANCE: f64 = 0.10;
+
+/// Confidence bump per matched watermark region. Lower than
+/// `PERCEPTUAL_MATCH_SCORE` since a region match is weaker evidence on its
+/// own (smaller hash, looser tolerance), but several regions can stack.
+const REGION_MATCH_SCORE: u32 = 35;
+
+/// Fractional crop windows (`x`, `y`, `width`, `height`, all relative to
+/// image dimensions) where TikTok typically burns in chrome: the
+/// "@username" caption pinned to the bottom-left, and the logo watermark,
+/// which TikTok roams between corners specifically to frustrate static
+/// cropping.
+const WATERMARK_REGIONS: &[(f64, f64, f64, f64, &str)] = &[
+    (0.0, 0.80, 0.60, 0.20, "bottom_left_caption"),
+    (0.0, 0.0, 0.25, 0.15, "top_left_corner"),
+    (0.75, 0.0, 0.25, 0.15, "top_right_corner"),
+    (0.0, 0.85, 0.25, 0.15, "bottom_left_corner"),
+    (0.75, 0.85, 0.25, 0.15, "bottom_right_corner"),
+];
 
 pub struct TikTokPhotoDetector {
     metadata_manager: MetadataManager,
+    hash_database: TikTokHashDatabase,
+    watermark_region_database: TikTokHashDatabase,
 }
 
 impl TikTokPhotoDetector {
     pub fn new() -> Result<Self> {
+        Self::from_metadata_manager(MetadataManager::new()?)
+    }
+
+    /// Same as `new`, but loads detection indicators and scoring rules from
+    /// a TOML or JSON ruleset file (see `rule_config::RuleSet`) instead of
+    /// the built-in ruleset.
+    pub fn new_with_ruleset_file(ruleset_path: &std::path::Path) -> Result<Self> {
+        Self::from_metadata_manager(MetadataManager::new_with_ruleset_file(ruleset_path)?)
+    }
+
+    fn from_metadata_manager(metadata_manager: MetadataManager) -> Result<Self> {
         Ok(TikTokPhotoDetector {
-            metadata_manager: MetadataManager::new()?,
+            metadata_manager,
+            // Bundled reference fingerprints are empty by default; callers can
+            // extend this by constructing `TikTokHashDatabase::from_entries`
+            // with hashes of confirmed TikTok exports/watermarked frames.
+            hash_database: TikTokHashDatabase::default(),
+            // No bundled region fingerprints ship yet (we don't have
+            // confirmed reference crops to hash); extend by writing
+            // `WatermarkHashEntry` JSON to `Self::user_watermark_hash_path()`.
+            watermark_region_database: TikTokHashDatabase::load(&[], Some(&Self::user_watermark_hash_path())),
         })
     }
 
+    /// Where users can drop a JSON list of `WatermarkHashEntry` reference
+    /// hashes for watermark/caption crops, to extend detection without a
+    /// rebuild.
+    fn user_watermark_hash_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("tiktok_watermark_region_hashes.json")
+    }
+
     pub fn analyze_image(&self, filepath: &Path) -> Result<FileMetadata> {
         let mut metadata = self.metadata_manager.analyze_file(filepath)?;
-        
+
         // Enhanced image-specific analysis
         self.enhance_image_analysis(&mut metadata);
-        
+
+        // `enhance_image_analysis` only looks at signals `metadata_manager`
+        // already gathered (extension, filename shape, size, dimensions) -
+        // effectively free compared to the perceptual hashing below, which
+        // has to open and decode the image. If those cheap signals alone
+        // already push confidence past "confirmed", the hash passes can't
+        // change the verdict, so skip reading the file a second (and third)
+        // time.
+        if metadata.tiktok_analysis.confidence_score >= 70 {
+            metadata.tiktok_analysis.evidence_found.push(
+                "Cheap signals already confirmed TikTok origin: skipped perceptual hash analysis".to_string()
+            );
+            return Ok(metadata);
+        }
+
+        self.check_perceptual_match(filepath, &mut metadata);
+        self.check_watermark_regions(filepath, &mut metadata);
+
         Ok(metadata)
     }
 
+    /// Crops the regions where TikTok typically burns in its logo/caption
+    /// chrome (see `WATERMARK_REGIONS`), hashes each with
+    /// `PerceptualHasher::dhash_region`, and checks it against the
+    /// watermark-region reference database. Run independently of
+    /// `check_perceptual_match` since a cropped region's dHash isn't
+    /// comparable to one computed over the whole frame. Skips a region
+    /// gracefully if the image is too small for its crop window.
+    fn check_watermark_regions(&self, filepath: &Path, metadata: &mut FileMetadata) {
+        for (x, y, w, h, region_label) in WATERMARK_REGIONS {
+            let hash = match PerceptualHasher::dhash_region(filepath, *x, *y, *w, *h) {
+                Ok(Some(hash)) => hash,
+                _ => continue,
+            };
+
+            if let Some((label, distance)) = self.watermark_region_database.check(hash, REGION_MATCH_TOLERANCE) {
+                metadata.tiktok_analysis.evidence_found.push(format!(
+                    "TikTok watermark detected in {} region (distance {}): {}",
+                    region_label, distance, label
+                ));
+                metadata.tiktok_analysis.indicators.insert(format!("watermark_hash_{}", region_label), format!("{:016x}", hash));
+                metadata.tiktok_analysis.confidence_score += REGION_MATCH_SCORE;
+            }
+        }
+
+        if metadata.tiktok_analysis.confidence_score >= 70 {
+            metadata.tiktok_analysis.is_tiktok = true;
+            metadata.tiktok_analysis.verdict = "CONFIRMED: File is from TikTok".to_string();
+        } else if metadata.tiktok_analysis.confidence_score >= 40 {
+            metadata.tiktok_analysis.is_tiktok = true;
+            metadata.tiktok_analysis.verdict = "LIKELY: Strong evidence suggests TikTok origin".to_string();
+        }
+    }
+
+    /// Computes the image's dHash and checks it against the known-TikTok
+    /// fingerprint database, bumping confidence on a match within tolerance.
+    fn check_perceptual_match(&self, filepath: &Path, metadata: &mut FileMetadata) {
+        let hash = match PerceptualHasher::dhash(filepath) {
+            Ok(h) => h,
+            Err(_) => return,
+        };
+
+        if let Some((label, distance)) = self.hash_database.check(hash, PERCEPTUAL_MATCH_TOLERANCE) {
+            metadata.tiktok_analysis.evidence_found.push(format!(
+                "perceptual match to known TikTok asset (distance {}): {}",
+                distance, label
+            ));
+            metadata.tiktok_analysis.indicators.insert("perceptual_hash".to_string(), format!("{:016x}", hash));
+            metadata.tiktok_analysis.confidence_score += PERCEPTUAL_MATCH_SCORE;
+
+            if metadata.tiktok_analysis.confidence_score >= 70 {
+                metadata.tiktok_analysis.is_tiktok = true;
+                metadata.tiktok_analysis.verdict = "CONFIRMED: File is from TikTok".to_string();
+            } else if metadata.tiktok_analysis.confidence_score >= 40 {
+                metadata.tiktok_analysis.is_tiktok = true;
+                metadata.tiktok_analysis.verdict = "LIKELY: Strong evidence suggests TikTok origin".to_string();
+            }
+        }
+    }
+
     fn enhance_image_analysis(&self, metadata: &mut FileMetadata) {
         let mut additional_score = 0;
         let mut additional_evidence = Vec::new();
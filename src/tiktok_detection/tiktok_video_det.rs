@@ -1,161 +1,380 @@
 use std::path::Path;
 use anyhow::Result;
-use crate::tiktok_detection::metadata_read::metadata_manager::{MetadataManager, FileMetadata};
+use walkdir::WalkDir;
+use crate::tiktok_detection::confidence_model::LogisticModel;
+use crate::tiktok_detection::metadata_read::metadata_manager::{MetadataManager, FileMetadata, AigcAnalysis};
+use crate::tiktok_detection::perceptual_hash::TikTokHashDatabase;
+use crate::tiktok_detection::video_frame_hash::VideoFrameSampler;
+use crate::tiktok_detection::video_duplicate::VideoDuplicateDetector;
+use crate::tiktok_detection::video_probe::VideoProbe;
+
+/// Normalized tolerance for matching a sampled frame's dHash against known
+/// TikTok watermark/logo fingerprints.
+const FRAME_MATCH_TOLERANCE: f64 = 0.06;
+
+/// Video extensions considered when walking a folder to train the
+/// confidence model, matching `scan_filter::VIDEO_EXTENSIONS`.
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "avi", "mkv", "flv", "webm"];
+
+/// Epochs, learning rate, and L2 strength for `train_confidence_model`.
+/// The labeled test folders this repo ships are small (hundreds of files at
+/// most), so full-batch gradient descent converges well within this budget
+/// without needing mini-batching or a learning-rate schedule.
+const TRAINING_EPOCHS: usize = 500;
+const LEARNING_RATE: f64 = 0.1;
+const L2_REGULARIZATION: f64 = 0.01;
+
+/// Embedded strings that mark a clip as AI-generated, independent of the
+/// generic TikTok-origin indicators in `FEATURE_NAMES`: TikTok's own AIGC
+/// label, content-credentials/C2PA provenance manifests, and known
+/// generator signatures seen in the wild.
+const AIGC_MARKERS: &[&str] = &[
+    "aigc_info",
+    "aigc_label_type",
+    "c2pa",
+    "content credentials",
+    "contentcredentials",
+    "capcut_ai",
+    "jianying_ai",
+];
+
+/// Named, independent predictors extracted from a video's evidence, in the
+/// fixed order `extract_features` always produces. Each corresponds to one
+/// rule from the original hand-tuned additive scorer.
+const FEATURE_NAMES: &[&str] = &[
+    "tiktok_standard_dimensions",
+    "exact_preferred_dimensions",
+    "portrait_orientation",
+    "vertical_mobile_aspect_ratio",
+    "portrait_aspect_ratio",
+    "bytedance_string",
+    "lavf_exact_string",
+    "lavf_string",
+    "mp4v_string",
+    "isom_string",
+    "douyin_string",
+    "musically_string",
+    "aigc_info_string",
+    "vid_md5_string",
+    "download_naming_pattern",
+    "typical_file_size",
+    "frame_watermark_ratio",
+];
 
 pub struct TikTokVideoDetector {
     metadata_manager: MetadataManager,
+    frame_sampler: VideoFrameSampler,
+    watermark_database: TikTokHashDatabase,
+    duplicate_detector: VideoDuplicateDetector,
+    confidence_model: LogisticModel,
+    video_probe: VideoProbe,
 }
 
 impl TikTokVideoDetector {
     pub fn new() -> Result<Self> {
+        Self::from_metadata_manager(MetadataManager::new()?)
+    }
+
+    /// Same as `new`, but loads detection indicators and scoring rules from
+    /// a TOML or JSON ruleset file (see `rule_config::RuleSet`) instead of
+    /// the built-in ruleset.
+    pub fn new_with_ruleset_file(ruleset_path: &Path) -> Result<Self> {
+        Self::from_metadata_manager(MetadataManager::new_with_ruleset_file(ruleset_path)?)
+    }
+
+    fn from_metadata_manager(metadata_manager: MetadataManager) -> Result<Self> {
         Ok(TikTokVideoDetector {
-            metadata_manager: MetadataManager::new()?,
+            metadata_manager,
+            frame_sampler: VideoFrameSampler::new(),
+            // Bundled watermark fingerprints are empty by default; extend via
+            // `TikTokHashDatabase::from_entries` with hashes of confirmed
+            // TikTok logo/caption chrome.
+            watermark_database: TikTokHashDatabase::default(),
+            duplicate_detector: VideoDuplicateDetector::new(),
+            // Load a previously trained model if `train_confidence_model` has
+            // been run; otherwise fall back to hand-tuned weights roughly
+            // mirroring the old additive bonuses, so detection still works
+            // out of the box on a fresh checkout.
+            confidence_model: LogisticModel::load_from(&Self::model_config_path())
+                .unwrap_or_else(Self::heuristic_fallback_model),
+            video_probe: VideoProbe::new(),
         })
     }
 
+    /// Where a trained confidence model is persisted/loaded from.
+    fn model_config_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("tiktok_video_confidence_model.json")
+    }
+
+    /// Hand-tuned weights used until `train_confidence_model` has produced a
+    /// real one. Roughly proportional to the old additive-scorer bonuses
+    /// (divided by 100 to sit in logistic-regression scale) so behavior is
+    /// similar in spirit, though not numerically identical, to the
+    /// pre-model heuristic.
+    fn heuristic_fallback_model() -> LogisticModel {
+        LogisticModel {
+            weights: vec![
+                0.30, // tiktok_standard_dimensions
+                0.15, // exact_preferred_dimensions
+                0.10, // portrait_orientation
+                0.20, // vertical_mobile_aspect_ratio
+                0.08, // portrait_aspect_ratio
+                0.25, // bytedance_string
+                0.20, // lavf_exact_string
+                0.10, // lavf_string
+                0.08, // mp4v_string
+                0.08, // isom_string
+                0.25, // douyin_string
+                0.08, // musically_string
+                0.40, // aigc_info_string
+                0.35, // vid_md5_string
+                0.25, // download_naming_pattern
+                0.05, // typical_file_size
+                1.50, // frame_watermark_ratio
+            ],
+            bias: -1.4,
+            threshold: 0.3,
+        }
+    }
+
     pub fn analyze_video(&self, filepath: &Path) -> Result<FileMetadata> {
         let mut metadata = self.metadata_manager.analyze_file(filepath)?;
-        
-        // Enhanced video-specific analysis
-        self.enhance_video_analysis(&mut metadata);
-        
+
+        // Camera photos are excluded upstream in `MetadataManager`; respect
+        // that verdict instead of running the model over them.
+        if metadata.tiktok_analysis.verdict.starts_with("EXCLUDED") {
+            return Ok(metadata);
+        }
+
+        // Feed genuine stream dimensions into the dimension/aspect-ratio
+        // features instead of whatever `MetadataManager`'s image-focused
+        // probing guessed (which usually can't read a video container at all).
+        if let Some(probe) = self.video_probe.probe(filepath) {
+            metadata.dimensions = Some((probe.width, probe.height));
+            metadata.aspect_ratio = Some(probe.width as f64 / probe.height as f64);
+            metadata.tiktok_analysis.indicators.insert("codec".to_string(), probe.codec_name.clone());
+            metadata.tiktok_analysis.indicators.insert("container_format".to_string(), probe.container_format.clone());
+            if !probe.decodable {
+                metadata.tiktok_analysis.evidence_found.push("ffmpeg could not decode the first/last frame: file may be truncated or corrupt".to_string());
+            }
+        }
+
+        let frame_watermark_ratio = self.sample_frame_watermark_ratio(filepath, &mut metadata);
+        self.enhance_video_analysis(&mut metadata, frame_watermark_ratio);
+        self.inspect_mp4_tags(filepath, &mut metadata);
+        metadata.aigc_analysis = Self::detect_aigc_markers(&metadata);
+
         Ok(metadata)
     }
 
-    fn enhance_video_analysis(&self, metadata: &mut FileMetadata) {
-        let mut additional_score = 0;
-        let mut additional_evidence = Vec::new();
+    /// Checks the MP4/MOV metadata atoms (`udta`/`meta`/`ilst`) for a TikTok
+    /// fingerprint via `VideoProbe::find_tiktok_tag`. A matched atom is a much
+    /// stronger signal than any filename/dimension heuristic, so it overrides
+    /// `enhance_video_analysis`'s model-based score straight into the
+    /// confirmed tier rather than just nudging a feature weight.
+    fn inspect_mp4_tags(&self, filepath: &Path, metadata: &mut FileMetadata) {
+        let Some(tag_match) = self.video_probe.find_tiktok_tag(filepath) else { return };
 
-        // Check for TikTok-specific video characteristics
-        if let Some((width, height)) = metadata.dimensions {
-            // TikTok's standard video dimensions
-            let tiktok_video_dimensions = [
-                (576, 1024), (576, 1246), (576, 1280),
-                (720, 1280), (1080, 1920),
-            ];
+        metadata.tiktok_analysis.evidence_found.push(format!(
+            "MP4 metadata atom matched: {}=\"{}\"", tag_match.atom, tag_match.value
+        ));
+        metadata.tiktok_analysis.indicators.insert("mp4_tag_atom".to_string(), tag_match.atom.clone());
+        metadata.tiktok_analysis.indicators.insert("mp4_tag_value".to_string(), tag_match.value.clone());
+        metadata.tiktok_analysis.confidence_score = metadata.tiktok_analysis.confidence_score.max(95);
+        metadata.tiktok_analysis.is_tiktok = true;
+        metadata.tiktok_analysis.verdict = "CONFIRMED: TikTok metadata atom matched".to_string();
+        metadata.mp4_tag_match = Some(tag_match);
+    }
 
-            if tiktok_video_dimensions.contains(&(width, height)) {
-                additional_evidence.push(format!("TikTok standard video dimensions: {}x{}", width, height));
-                additional_score += 30;
-            }
+    /// Scans `metadata.strings_found` for `AIGC_MARKERS`, independent of the
+    /// generic TikTok-origin model: a clip can be a confirmed TikTok upload
+    /// and separately flagged (or not) as AI-generated, so this runs as its
+    /// own track rather than feeding `enhance_video_analysis`.
+    fn detect_aigc_markers(metadata: &FileMetadata) -> AigcAnalysis {
+        let lowercased_strings: Vec<String> = metadata.strings_found.iter().map(|s| s.to_lowercase()).collect();
 
-            // Bonus for exact TikTok preferred dimensions
-            if (width, height) == (576, 1024) || (width, height) == (1080, 1920) {
-                additional_evidence.push("Exact TikTok preferred video dimensions".to_string());
-                additional_score += 15;
+        let evidence_found: Vec<String> = AIGC_MARKERS.iter()
+            .filter(|marker| lowercased_strings.iter().any(|s| s.contains(*marker)))
+            .map(|marker| format!("AIGC marker found: {}", marker))
+            .collect();
+
+        let is_ai_generated = !evidence_found.is_empty();
+        let verdict = if is_ai_generated {
+            "CONFIRMED: AI-generated content markers detected".to_string()
+        } else {
+            "NONE: No AI-generation markers found".to_string()
+        };
+
+        AigcAnalysis { is_ai_generated, evidence_found, verdict }
+    }
+
+    /// Samples evenly-spaced frames via ffmpeg and checks each one's
+    /// perceptual hash against the known TikTok watermark database, pushing
+    /// human-readable evidence and returning the fraction of frames that
+    /// matched (`0.0` if ffmpeg is missing or nothing matched) for use as a
+    /// model feature.
+    fn sample_frame_watermark_ratio(&self, filepath: &Path, metadata: &mut FileMetadata) -> f64 {
+        let frame_hashes = match self.frame_sampler.sample_frame_hashes(filepath) {
+            Ok(Some(hashes)) => hashes,
+            Ok(None) => {
+                metadata.tiktok_analysis.evidence_found.push("ffmpeg not found: skipped frame-based watermark analysis".to_string());
+                return 0.0;
             }
+            Err(_) => return 0.0,
+        };
 
-            if let Some(ratio) = metadata.aspect_ratio {
-                // Relaxed: portrait if width < height, or aspect ratio < 0.8
-                if width < height {
-                    additional_evidence.push("Portrait orientation (width < height)".to_string());
-                    additional_score += 10;
-                }
-                if (0.55..=0.58).contains(&ratio) {
-                    additional_evidence.push("Vertical mobile video format (9:16)".to_string());
-                    additional_score += 20;
-                } else if ratio < 0.8 {
-                    additional_evidence.push(format!("Portrait aspect ratio: {:.3}", ratio));
-                    additional_score += 8;
-                }
+        if frame_hashes.is_empty() {
+            return 0.0;
+        }
+
+        let mut matched_frames = 0;
+        let mut best_distance = u32::MAX;
+        for hash in &frame_hashes {
+            if let Some((_, distance)) = self.watermark_database.check(*hash, FRAME_MATCH_TOLERANCE) {
+                matched_frames += 1;
+                best_distance = best_distance.min(distance);
             }
         }
 
-        // Check for TikTok-specific strings in metadata
-        // Check for TikTok-specific video characteristics
+        if matched_frames > 0 {
+            metadata.tiktok_analysis.evidence_found.push(format!(
+                "TikTok watermark detected in {}/{} sampled frames (best distance {})",
+                matched_frames, frame_hashes.len(), best_distance
+            ));
+        }
+
+        matched_frames as f64 / frame_hashes.len() as f64
+    }
+
+    /// Builds the named feature vector (see `FEATURE_NAMES`) for `metadata`,
+    /// given the frame-watermark ratio already computed for it.
+    fn extract_features(metadata: &FileMetadata, frame_watermark_ratio: f64) -> Vec<f64> {
+        let mut features = vec![0.0; FEATURE_NAMES.len()];
+
         if let Some((width, height)) = metadata.dimensions {
-            // TikTok's standard video dimensions
             let tiktok_video_dimensions = [
                 (576, 1024), (576, 1246), (576, 1280),
                 (720, 1280), (1080, 1920),
             ];
 
             if tiktok_video_dimensions.contains(&(width, height)) {
-                additional_evidence.push(format!("TikTok standard video dimensions: {}x{}", width, height));
-                additional_score += 30;
+                features[0] = 1.0;
             }
-
-            // Bonus for exact TikTok preferred dimensions
             if (width, height) == (576, 1024) || (width, height) == (1080, 1920) {
-                additional_evidence.push("Exact TikTok preferred video dimensions".to_string());
-                additional_score += 15;
+                features[1] = 1.0;
             }
 
             if let Some(ratio) = metadata.aspect_ratio {
-                // Relaxed: portrait if width < height, or aspect ratio < 0.8
                 if width < height {
-                    additional_evidence.push("Portrait orientation (width < height)".to_string());
-                    additional_score += 10;
+                    features[2] = 1.0;
                 }
                 if (0.55..=0.58).contains(&ratio) {
-                    additional_evidence.push("Vertical mobile video format (9:16)".to_string());
-                    additional_score += 20;
+                    features[3] = 1.0;
                 } else if ratio < 0.8 {
-                    additional_evidence.push(format!("Portrait aspect ratio: {:.3}", ratio));
-                    additional_score += 8;
+                    features[4] = 1.0;
                 }
             }
         }
-        let filename = &metadata.filename;
-        // Check for TikTok-specific strings in metadata
-        let tiktok_specific_strings = [
-            "ByteDance",
-            "Lavf58.76.100", // Common TikTok encoder
-            "Lavf", // General FFmpeg encoder, TikTok often uses Lavf*
-            "mp4v", // TikTok encoder
-            "isom", // TikTok encoder
-            "Douyin",
-            "Musical.ly",
-            "aigc_info",
-            "vid_md5",
-        ];
-        for string in &metadata.strings_found {
-            for tiktok_string in &tiktok_specific_strings {
-                if string.to_lowercase().contains(&tiktok_string.to_lowercase()) {
-                    additional_evidence.push(format!("TikTok-specific metadata: {}", tiktok_string));
-                    additional_score += match tiktok_string.as_ref() {
-                        "aigc_info" => 40,
-                        "vid_md5" => 35,
-                        "ByteDance" => 25,
-                        "TikTok" => 25,
-                        "Douyin" => 25,
-                        "Lavf58.76.100" => 20,
-                        "Lavf" => 10,
-                        "mp4v" => 8,
-                        "isom" => 8,
-                        "Musical.ly" => 8,
-                        _ => 10,
-                    };
-                    break;
-                }
-            }
-        }
-        if filename.to_lowercase().starts_with("download") && filename.to_lowercase().ends_with(".mp4") {
-            additional_evidence.push("TikTok download naming pattern (Download*.mp4)".to_string());
-            additional_score += 25; // Boosted from 10 to 25
+
+        let lowercased_strings: Vec<String> = metadata.strings_found.iter().map(|s| s.to_lowercase()).collect();
+        let string_matches = |needle: &str| lowercased_strings.iter().any(|s| s.contains(needle));
+
+        features[5] = string_matches("bytedance") as u8 as f64;
+        features[6] = string_matches("lavf58.76.100") as u8 as f64;
+        features[7] = string_matches("lavf") as u8 as f64;
+        features[8] = string_matches("mp4v") as u8 as f64;
+        features[9] = string_matches("isom") as u8 as f64;
+        features[10] = string_matches("douyin") as u8 as f64;
+        features[11] = string_matches("musical.ly") as u8 as f64;
+        features[12] = string_matches("aigc_info") as u8 as f64;
+        features[13] = string_matches("vid_md5") as u8 as f64;
+
+        let lower_filename = metadata.filename.to_lowercase();
+        if lower_filename.starts_with("download") && lower_filename.ends_with(".mp4") {
+            features[14] = 1.0;
         }
 
-        // Check for reasonable file size (TikTok videos are typically 1-50MB)
         if metadata.size_bytes > 100_000 && metadata.size_bytes < 50_000_000 {
-            additional_evidence.push("File size typical of TikTok video".to_string());
-            additional_score += 5;
+            features[15] = 1.0;
+        }
+
+        features[16] = frame_watermark_ratio;
+
+        features
+    }
+
+    /// Runs the calibrated model over `metadata`'s features and records the
+    /// resulting probability (scaled to the existing 0-100 confidence
+    /// scale), evidence, and verdict - replacing the old hand-tuned additive
+    /// bonuses entirely.
+    fn enhance_video_analysis(&self, metadata: &mut FileMetadata, frame_watermark_ratio: f64) {
+        let features = Self::extract_features(metadata, frame_watermark_ratio);
+        let probability = self.confidence_model.predict_proba(&features);
+        let is_tiktok = probability >= self.confidence_model.threshold;
+
+        for (name, value) in FEATURE_NAMES.iter().zip(&features) {
+            if *value > 0.0 {
+                metadata.tiktok_analysis.evidence_found.push(format!("Feature fired: {} ({:.2})", name, value));
+            }
+        }
+
+        metadata.tiktok_analysis.indicators.insert("model_probability".to_string(), format!("{:.3}", probability));
+        metadata.tiktok_analysis.confidence_score = (probability * 100.0).round() as u32;
+        metadata.tiktok_analysis.is_tiktok = is_tiktok;
+        metadata.tiktok_analysis.verdict = if !is_tiktok {
+            "UNLIKELY: Insufficient evidence for TikTok origin".to_string()
+        } else if probability >= 0.7 {
+            "CONFIRMED: Video is from TikTok".to_string()
+        } else if probability >= 0.4 {
+            "LIKELY: Strong evidence suggests TikTok origin".to_string()
+        } else {
+            "POSSIBLE: Some TikTok-like characteristics found".to_string()
+        };
+    }
+
+    /// Extracts the feature vector for `filepath` without touching the
+    /// detector's loaded model, for use as one training sample.
+    fn extract_features_for_training(&self, filepath: &Path) -> Result<Vec<f64>> {
+        let mut metadata = self.metadata_manager.analyze_file(filepath)?;
+        if let Some(probe) = self.video_probe.probe(filepath) {
+            metadata.dimensions = Some((probe.width, probe.height));
+            metadata.aspect_ratio = Some(probe.width as f64 / probe.height as f64);
         }
+        let frame_watermark_ratio = self.sample_frame_watermark_ratio(filepath, &mut metadata);
+        Ok(Self::extract_features(&metadata, frame_watermark_ratio))
+    }
 
-        // Update the analysis with additional findings
-        metadata.tiktok_analysis.confidence_score += additional_score;
-        metadata.tiktok_analysis.evidence_found.extend(additional_evidence);
-
-        // Re-evaluate verdict with enhanced analysis
-        if metadata.tiktok_analysis.confidence_score >= 70 {
-            metadata.tiktok_analysis.is_tiktok = true;
-            metadata.tiktok_analysis.verdict = "CONFIRMED: Video is from TikTok".to_string();
-        } else if metadata.tiktok_analysis.confidence_score >= 40 {
-            metadata.tiktok_analysis.is_tiktok = true;
-            metadata.tiktok_analysis.verdict = "LIKELY: Strong evidence suggests TikTok origin".to_string();
-        } else if metadata.tiktok_analysis.confidence_score >= 14 {
-            metadata.tiktok_analysis.is_tiktok = true;
-            metadata.tiktok_analysis.verdict = "POSSIBLE: Some TikTok-like characteristics found".to_string();
+    /// Trains a new confidence model from videos in `tiktok_folder` (label =
+    /// true) and `not_tiktok_folder` (label = false) via gradient descent on
+    /// log-loss, picks a decision threshold by maximizing Youden's J, and
+    /// persists the result to `Self::model_config_path()` so the next
+    /// `TikTokVideoDetector::new()` loads it instead of the heuristic
+    /// fallback. Lets users retrain on their own labeled data.
+    pub fn train_confidence_model(&self, tiktok_folder: &Path, not_tiktok_folder: &Path) -> Result<LogisticModel> {
+        let mut samples = Vec::new();
+
+        for (folder, label) in [(tiktok_folder, true), (not_tiktok_folder, false)] {
+            for entry in WalkDir::new(folder).into_iter().filter_map(|e| e.ok()) {
+                let path = entry.path();
+                let is_video = path.extension()
+                    .and_then(|ext| ext.to_str())
+                    .map(|ext| VIDEO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+                    .unwrap_or(false);
+
+                if path.is_file() && is_video {
+                    if let Ok(features) = self.extract_features_for_training(path) {
+                        samples.push((features, label));
+                    }
+                }
+            }
         }
+
+        let model = LogisticModel::train(&samples, TRAINING_EPOCHS, LEARNING_RATE, L2_REGULARIZATION);
+        model.save_to(&Self::model_config_path())?;
+        println!("🧠 Trained confidence model on {} videos (threshold {:.2}), saved to {}",
+                 samples.len(), model.threshold, Self::model_config_path().display());
+
+        Ok(model)
     }
 
     pub fn generate_summary(&self, results: &[FileMetadata]) -> String {
@@ -194,6 +413,32 @@ impl TikTokVideoDetector {
             }
         }
 
+        // Group re-downloads/reposts of the same source clip so the user can
+        // see "N copies of the same video" instead of N unrelated entries.
+        let duplicate_groups = self.duplicate_detector.find_duplicate_groups(results);
+        if !duplicate_groups.is_empty() {
+            summary.push_str(&format!("ðŸ” Duplicate Videos: {} group(s)\n", duplicate_groups.len()));
+            for (i, group) in duplicate_groups.iter().enumerate() {
+                summary.push_str(&format!("  Group {}: {} copies of the same video\n", i + 1, group.len()));
+                for file in group {
+                    summary.push_str(&format!("    â€¢ {}\n", file.filename));
+                }
+            }
+            summary.push('\n');
+        }
+
+        // AI-generation is its own track, reported independently of the
+        // TikTok-origin tiers above - a clip can be both, either, or neither.
+        let ai_generated = results.iter().filter(|r| r.aigc_analysis.is_ai_generated).count();
+        if ai_generated > 0 {
+            summary.push_str(&format!("ðŸ¤– AI-Generated Content: {} video(s)\n", ai_generated));
+            for result in results.iter().filter(|r| r.aigc_analysis.is_ai_generated) {
+                summary.push_str(&format!("  â€¢ {} ({})\n", result.filename, result.aigc_analysis.verdict));
+                summary.push_str(&format!("    Evidence: {}\n", result.aigc_analysis.evidence_found.join(", ")));
+            }
+            summary.push('\n');
+        }
+
         summary
     }
 
@@ -218,6 +463,16 @@ impl TikTokVideoDetector {
             }
         }
 
+        // Reported independently of the TikTok-origin verdict above: AI
+        // generation is a separate track with its own evidence.
+        println!("\nðŸ¤– AIGC RESULT: {}", metadata.aigc_analysis.verdict);
+        if !metadata.aigc_analysis.evidence_found.is_empty() {
+            println!("   Evidence:");
+            for (i, evidence) in metadata.aigc_analysis.evidence_found.iter().enumerate() {
+                println!("   {}. {}", i + 1, evidence);
+            }
+        }
+
         println!("\nðŸ“± Technical Details:");
         if let Some((w, h)) = metadata.dimensions {
             println!("   â€¢ Dimensions: {}x{}", w, h);
@@ -243,10 +498,15 @@ impl TikTokVideoDetector {
         println!();
     }
 
+    /// Verifies a video can actually be decoded, via `VideoProbe`'s
+    /// first/last-frame check. Falls back to the old file-size heuristic
+    /// when `VideoProbe::probe` returns nothing (ffprobe isn't installed or
+    /// finds no readable video stream).
     pub fn check_video_integrity(&self, filepath: &Path) -> Result<bool> {
-        // Basic check to see if the video file can be opened
-        // This would typically use ffmpeg or similar library
-        // For now, just check if file exists and has reasonable size
+        if let Some(probe) = self.video_probe.probe(filepath) {
+            return Ok(probe.decodable);
+        }
+
         let metadata = std::fs::metadata(filepath)?;
         Ok(metadata.len() > 1000 && metadata.len() < 100_000_000) // 1KB to 100MB
     }
@@ -0,0 +1,71 @@
+/// Tracks connected components so items that are mutually related - even
+/// transitively, through a third related item - end up in the same group.
+/// Shared by `video_duplicate` and `image_duplicate`'s near-duplicate
+/// clustering, which both reduce to "union items within tolerance of each
+/// other, then read off connected components" over a caller-supplied index
+/// range rather than the items themselves.
+pub struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    pub fn new(size: usize) -> Self {
+        UnionFind { parent: (0..size).collect() }
+    }
+
+    pub fn find(&mut self, x: usize) -> usize {
+        if self.parent[x] != x {
+            self.parent[x] = self.find(self.parent[x]);
+        }
+        self.parent[x]
+    }
+
+    pub fn union(&mut self, a: usize, b: usize) {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a != root_b {
+            self.parent[root_a] = root_b;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_with_every_item_in_its_own_group() {
+        let mut uf = UnionFind::new(4);
+        for i in 0..4 {
+            assert_eq!(uf.find(i), i);
+        }
+    }
+
+    #[test]
+    fn union_merges_two_items_into_the_same_root() {
+        let mut uf = UnionFind::new(3);
+        uf.union(0, 1);
+        assert_eq!(uf.find(0), uf.find(1));
+        assert_ne!(uf.find(0), uf.find(2));
+    }
+
+    #[test]
+    fn union_is_transitive_through_a_shared_item() {
+        let mut uf = UnionFind::new(5);
+        uf.union(0, 1);
+        uf.union(1, 2);
+        // 0 and 2 were never unioned directly, only through 1.
+        assert_eq!(uf.find(0), uf.find(2));
+        assert_ne!(uf.find(0), uf.find(3));
+        assert_ne!(uf.find(0), uf.find(4));
+    }
+
+    #[test]
+    fn unioning_already_connected_items_is_a_no_op() {
+        let mut uf = UnionFind::new(3);
+        uf.union(0, 1);
+        let root_before = uf.find(0);
+        uf.union(1, 0);
+        assert_eq!(uf.find(0), root_before);
+        assert_eq!(uf.find(1), root_before);
+    }
+}
@@ -0,0 +1,356 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use crate::tiktok_detection::metadata_read::metadata_manager::FileMetadata;
+use crate::tiktok_detection::union_find::UnionFind;
+
+/// Number of evenly-spaced frames sampled across a video's duration to build
+/// its perceptual hash. More frames make the hash more specific to the
+/// actual content (rather than a single representative thumbnail), at the
+/// cost of one ffmpeg seek-and-decode per frame.
+const SAMPLE_FRAME_COUNT: usize = 9;
+
+/// Side length of the grayscale grid each sampled frame is downscaled to
+/// before the DCT is taken.
+const DCT_INPUT_SIZE: usize = 32;
+
+/// Low-frequency DCT coefficients kept per frame (an 8x8 block in the
+/// top-left corner, i.e. the lowest spatial frequencies).
+const DCT_KEEP: usize = 8;
+
+/// Default Hamming-distance tolerance, out of `SAMPLE_FRAME_COUNT` frame
+/// codes, for two videos to be considered copies of the same content.
+pub const DEFAULT_DUPLICATE_TOLERANCE: u32 = 10;
+
+/// A fixed-width perceptual fingerprint for a video: one DCT-based hash per
+/// sampled frame, concatenated in timestamp order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VideoHash(Vec<u64>);
+
+impl VideoHash {
+    /// Hamming distance between two video hashes: the sum of per-frame
+    /// Hamming distances. Each term satisfies the triangle inequality, so
+    /// the sum does too, which is what lets the BK-tree below prune safely.
+    pub fn distance(&self, other: &VideoHash) -> u32 {
+        self.0.iter().zip(other.0.iter())
+            .map(|(a, b)| (a ^ b).count_ones())
+            .sum()
+    }
+}
+
+/// On-disk cache of per-video perceptual hashes, keyed on path + size +
+/// modified time so re-scans skip the expensive ffmpeg decode + DCT pass.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VideoHashCache {
+    entries: HashMap<String, VideoHash>,
+}
+
+impl VideoHashCache {
+    fn cache_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("tiktok_video_dedup_hash_cache.json")
+    }
+
+    fn load() -> Self {
+        let path = Self::cache_path();
+        fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        if let Ok(content) = serde_json::to_string(self) {
+            let _ = fs::write(Self::cache_path(), content);
+        }
+    }
+
+    fn key_for(filepath: &Path) -> Option<String> {
+        let metadata = fs::metadata(filepath).ok()?;
+        let modified = metadata.modified().ok()?;
+        let modified_secs = modified.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+        Some(format!("{}|{}|{}", filepath.to_string_lossy(), metadata.len(), modified_secs))
+    }
+}
+
+/// A node in a BK-tree of `VideoHash`es, indexed by position in the caller's
+/// result slice rather than a label string (duplicate groups need every
+/// match, not just the closest one).
+struct VideoBkNode {
+    hash: VideoHash,
+    index: usize,
+    children: Vec<(u32, VideoBkNode)>,
+}
+
+/// BK-tree over `VideoHash::distance`, used to find every reference hash
+/// within a tolerance instead of a single nearest match.
+struct VideoBkTree {
+    root: Option<VideoBkNode>,
+}
+
+impl VideoBkTree {
+    fn new() -> Self {
+        VideoBkTree { root: None }
+    }
+
+    fn insert(&mut self, hash: VideoHash, index: usize) {
+        match &mut self.root {
+            None => self.root = Some(VideoBkNode { hash, index, children: Vec::new() }),
+            Some(root) => Self::insert_node(root, hash, index),
+        }
+    }
+
+    fn insert_node(node: &mut VideoBkNode, hash: VideoHash, index: usize) {
+        let distance = node.hash.distance(&hash);
+        if let Some((_, child)) = node.children.iter_mut().find(|(d, _)| *d == distance) {
+            Self::insert_node(child, hash, index);
+        } else {
+            node.children.push((distance, VideoBkNode { hash, index, children: Vec::new() }));
+        }
+    }
+
+    /// Returns the index of every entry within `tolerance` bits of `hash`.
+    fn find_all_within(&self, hash: &VideoHash, tolerance: u32) -> Vec<usize> {
+        let mut matches = Vec::new();
+        if let Some(root) = &self.root {
+            Self::search_node(root, hash, tolerance, &mut matches);
+        }
+        matches
+    }
+
+    fn search_node(node: &VideoBkNode, hash: &VideoHash, tolerance: u32, matches: &mut Vec<usize>) {
+        let distance = node.hash.distance(hash);
+        if distance <= tolerance {
+            matches.push(node.index);
+        }
+
+        let low = distance.saturating_sub(tolerance);
+        let high = distance + tolerance;
+        for (edge, child) in &node.children {
+            if *edge >= low && *edge <= high {
+                Self::search_node(child, hash, tolerance, matches);
+            }
+        }
+    }
+}
+
+/// Finds near-duplicate TikTok videos (re-downloads, reposts) by computing a
+/// perceptual hash from sampled frames and grouping files whose hashes fall
+/// within a Hamming-distance tolerance of each other.
+pub struct VideoDuplicateDetector {
+    cache: std::sync::Mutex<VideoHashCache>,
+    ffmpeg_available: bool,
+}
+
+impl VideoDuplicateDetector {
+    pub fn new() -> Self {
+        let ffmpeg_available = Command::new("ffmpeg")
+            .arg("-version")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+
+        VideoDuplicateDetector {
+            cache: std::sync::Mutex::new(VideoHashCache::load()),
+            ffmpeg_available,
+        }
+    }
+
+    /// Computes (or loads from cache) the perceptual hash for one video.
+    /// Returns `Ok(None)` when ffmpeg isn't installed or the video's
+    /// duration can't be determined, so callers can skip it gracefully.
+    pub fn hash_video(&self, filepath: &Path) -> Result<Option<VideoHash>> {
+        if !self.ffmpeg_available {
+            return Ok(None);
+        }
+
+        if let Some(key) = VideoHashCache::key_for(filepath) {
+            if let Some(cached) = self.cache.lock().unwrap().entries.get(&key) {
+                return Ok(Some(cached.clone()));
+            }
+        }
+
+        let Some(duration_secs) = Self::probe_duration_seconds(filepath) else {
+            return Ok(None);
+        };
+
+        let mut frame_codes = Vec::with_capacity(SAMPLE_FRAME_COUNT);
+        for i in 0..SAMPLE_FRAME_COUNT {
+            let timestamp = duration_secs * (i + 1) as f64 / (SAMPLE_FRAME_COUNT + 1) as f64;
+            if let Some(code) = Self::hash_frame_at(filepath, timestamp) {
+                frame_codes.push(code);
+            }
+        }
+
+        if frame_codes.len() != SAMPLE_FRAME_COUNT {
+            // Couldn't reliably sample every frame (truncated/corrupt video).
+            return Ok(None);
+        }
+
+        let hash = VideoHash(frame_codes);
+
+        if let Some(key) = VideoHashCache::key_for(filepath) {
+            let mut cache = self.cache.lock().unwrap();
+            cache.entries.insert(key, hash.clone());
+            cache.save();
+        }
+
+        Ok(Some(hash))
+    }
+
+    /// Groups `results` whose video hashes fall within `DEFAULT_DUPLICATE_TOLERANCE`
+    /// bits of each other. Files that can't be hashed (no ffmpeg, not a video,
+    /// unreadable) are simply excluded from every group.
+    pub fn find_duplicate_groups(&self, results: &[FileMetadata]) -> Vec<Vec<FileMetadata>> {
+        self.find_duplicate_groups_with_tolerance(results, DEFAULT_DUPLICATE_TOLERANCE)
+    }
+
+    /// Same as `find_duplicate_groups`, but with an explicit tolerance
+    /// (0-`SAMPLE_FRAME_COUNT * 64` bits; the default of 10 works well for
+    /// re-encodes and re-uploads of the same source clip).
+    pub fn find_duplicate_groups_with_tolerance(&self, results: &[FileMetadata], tolerance: u32) -> Vec<Vec<FileMetadata>> {
+        let mut hashes: Vec<(usize, VideoHash)> = Vec::new();
+        for (index, metadata) in results.iter().enumerate() {
+            if let Ok(Some(hash)) = self.hash_video(Path::new(&metadata.filepath)) {
+                hashes.push((index, hash));
+            }
+        }
+
+        let mut tree = VideoBkTree::new();
+        for (index, hash) in &hashes {
+            tree.insert(hash.clone(), *index);
+        }
+
+        let mut union_find = UnionFind::new(results.len());
+        for (index, hash) in &hashes {
+            for matched_index in tree.find_all_within(hash, tolerance) {
+                union_find.union(*index, matched_index);
+            }
+        }
+
+        let mut groups: HashMap<usize, Vec<FileMetadata>> = HashMap::new();
+        for (index, _) in &hashes {
+            let root = union_find.find(*index);
+            groups.entry(root).or_default().push(results[*index].clone());
+        }
+
+        groups.into_values().filter(|group| group.len() > 1).collect()
+    }
+
+    fn probe_duration_seconds(filepath: &Path) -> Option<f64> {
+        let output = Command::new("ffmpeg")
+            .arg("-i")
+            .arg(filepath)
+            .output()
+            .ok()?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let duration_pattern = Regex::new(r"Duration:\s*(\d+):(\d+):(\d+\.\d+)").ok()?;
+        let captures = duration_pattern.captures(&stderr)?;
+
+        let hours: f64 = captures[1].parse().ok()?;
+        let minutes: f64 = captures[2].parse().ok()?;
+        let seconds: f64 = captures[3].parse().ok()?;
+        Some(hours * 3600.0 + minutes * 60.0 + seconds)
+    }
+
+    /// Seeks to `timestamp_secs`, decodes a single frame, downscales it to a
+    /// grayscale `DCT_INPUT_SIZE` grid, and returns its DCT-based perceptual
+    /// hash.
+    fn hash_frame_at(filepath: &Path, timestamp_secs: f64) -> Option<u64> {
+        let temp_path = std::env::temp_dir().join(format!(
+            "tiktok_dedup_frame_{}_{}.png",
+            filepath.file_stem().and_then(|s| s.to_str()).unwrap_or("video"),
+            (timestamp_secs * 1000.0) as u64
+        ));
+
+        let status = Command::new("ffmpeg")
+            .args(["-y", "-ss", &format!("{:.3}", timestamp_secs), "-i"])
+            .arg(filepath)
+            .args(["-frames:v", "1"])
+            .arg(&temp_path)
+            .output()
+            .ok()?;
+
+        if !status.status.success() || !temp_path.exists() {
+            let _ = fs::remove_file(&temp_path);
+            return None;
+        }
+
+        let img = image::open(&temp_path).ok();
+        let _ = fs::remove_file(&temp_path);
+        let img = img?;
+
+        Some(Self::dct_hash(&img))
+    }
+
+    /// Computes the DCT-based perceptual hash of a single already-decoded
+    /// frame: downscale to a `DCT_INPUT_SIZE` grayscale grid, take the 2D
+    /// DCT-II, keep the `DCT_KEEP`x`DCT_KEEP` lowest-frequency coefficients
+    /// (dropping the DC term), and threshold each against their median.
+    fn dct_hash(img: &image::DynamicImage) -> u64 {
+        let small = img
+            .resize_exact(DCT_INPUT_SIZE as u32, DCT_INPUT_SIZE as u32, image::imageops::FilterType::Triangle)
+            .to_luma8();
+
+        let mut pixels = [[0.0f64; DCT_INPUT_SIZE]; DCT_INPUT_SIZE];
+        for x in 0..DCT_INPUT_SIZE {
+            for y in 0..DCT_INPUT_SIZE {
+                pixels[x][y] = small.get_pixel(x as u32, y as u32)[0] as f64;
+            }
+        }
+
+        let mut coeffs = [[0.0f64; DCT_KEEP]; DCT_KEEP];
+        for (u, row) in coeffs.iter_mut().enumerate() {
+            for (v, coeff) in row.iter_mut().enumerate() {
+                let mut sum = 0.0;
+                for (x, column) in pixels.iter().enumerate() {
+                    for (y, &pixel) in column.iter().enumerate() {
+                        sum += pixel
+                            * ((std::f64::consts::PI / DCT_INPUT_SIZE as f64) * (x as f64 + 0.5) * u as f64).cos()
+                            * ((std::f64::consts::PI / DCT_INPUT_SIZE as f64) * (y as f64 + 0.5) * v as f64).cos();
+                    }
+                }
+                let cu = if u == 0 { 1.0 / 2f64.sqrt() } else { 1.0 };
+                let cv = if v == 0 { 1.0 / 2f64.sqrt() } else { 1.0 };
+                *coeff = 0.25 * cu * cv * sum;
+            }
+        }
+
+        let mut ac_terms: Vec<f64> = Vec::with_capacity(DCT_KEEP * DCT_KEEP - 1);
+        for u in 0..DCT_KEEP {
+            for v in 0..DCT_KEEP {
+                if u == 0 && v == 0 {
+                    continue;
+                }
+                ac_terms.push(coeffs[u][v]);
+            }
+        }
+        ac_terms.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let median = ac_terms[ac_terms.len() / 2];
+
+        let mut hash: u64 = 0;
+        let mut bit = 0;
+        for u in 0..DCT_KEEP {
+            for v in 0..DCT_KEEP {
+                if u == 0 && v == 0 {
+                    continue;
+                }
+                if coeffs[u][v] > median {
+                    hash |= 1 << bit;
+                }
+                bit += 1;
+            }
+        }
+
+        hash
+    }
+}
+
+impl Default for VideoDuplicateDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
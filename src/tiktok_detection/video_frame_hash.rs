@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use crate::tiktok_detection::perceptual_hash::PerceptualHasher;
+
+/// Number of evenly-spaced frames sampled from a video for watermark analysis.
+const SAMPLE_FRAME_COUNT: u32 = 5;
+
+/// On-disk cache of per-video frame hashes, keyed on path + size + modified
+/// time so re-scans skip the expensive ffmpeg decode.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FrameHashCache {
+    entries: HashMap<String, Vec<u64>>,
+}
+
+impl FrameHashCache {
+    fn cache_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("tiktok_frame_hash_cache.json")
+    }
+
+    fn load() -> Self {
+        let path = Self::cache_path();
+        fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        if let Ok(content) = serde_json::to_string(self) {
+            let _ = fs::write(Self::cache_path(), content);
+        }
+    }
+
+    fn key_for(filepath: &Path) -> Option<String> {
+        let metadata = fs::metadata(filepath).ok()?;
+        let modified = metadata.modified().ok()?;
+        let modified_secs = modified.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+        Some(format!("{}|{}|{}", filepath.to_string_lossy(), metadata.len(), modified_secs))
+    }
+}
+
+/// Samples evenly-spaced frames from a video via `ffmpeg` and computes a
+/// perceptual hash for each, so frame-burned watermarks (the bouncing
+/// `@username` + logo) can be matched even when container metadata has
+/// been stripped.
+pub struct VideoFrameSampler {
+    cache: std::sync::Mutex<FrameHashCache>,
+    ffmpeg_available: bool,
+}
+
+impl VideoFrameSampler {
+    pub fn new() -> Self {
+        let ffmpeg_available = Command::new("ffmpeg")
+            .arg("-version")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+
+        VideoFrameSampler {
+            cache: std::sync::Mutex::new(FrameHashCache::load()),
+            ffmpeg_available,
+        }
+    }
+
+    /// Extracts `SAMPLE_FRAME_COUNT` evenly-spaced frames from `filepath` and
+    /// returns their dHash fingerprints. Returns `Ok(None)` (rather than an
+    /// error) when ffmpeg isn't installed, so callers can degrade gracefully.
+    pub fn sample_frame_hashes(&self, filepath: &Path) -> Result<Option<Vec<u64>>> {
+        if !self.ffmpeg_available {
+            return Ok(None);
+        }
+
+        if let Some(key) = Self::key_for_cache(filepath) {
+            if let Some(cached) = self.cache.lock().unwrap().entries.get(&key) {
+                return Ok(Some(cached.clone()));
+            }
+        }
+
+        let temp_dir = std::env::temp_dir().join(format!(
+            "tiktok_frames_{}",
+            filepath.file_stem().and_then(|s| s.to_str()).unwrap_or("video")
+        ));
+        fs::create_dir_all(&temp_dir).context("Failed to create temp frame directory")?;
+
+        let frame_pattern = temp_dir.join("frame_%02d.png");
+        let status = Command::new("ffmpeg")
+            .args([
+                "-y",
+                "-i",
+            ])
+            .arg(filepath)
+            .args([
+                "-vf",
+                &format!("select='not(mod(n\\,{}))'", SAMPLE_FRAME_COUNT),
+                "-vsync",
+                "vfr",
+                "-frames:v",
+            ])
+            .arg(SAMPLE_FRAME_COUNT.to_string())
+            .arg(&frame_pattern)
+            .output()
+            .context("Failed to invoke ffmpeg")?;
+
+        let mut hashes = Vec::new();
+        if status.status.success() {
+            for entry in fs::read_dir(&temp_dir).context("Failed to read extracted frames")? {
+                let entry = entry?;
+                if let Ok(img) = image::open(entry.path()) {
+                    if let Ok(hash) = PerceptualHasher::dhash_image(&img) {
+                        hashes.push(hash);
+                    }
+                }
+            }
+        }
+
+        let _ = fs::remove_dir_all(&temp_dir);
+
+        if let Some(key) = Self::key_for_cache(filepath) {
+            self.cache.lock().unwrap().entries.insert(key, hashes.clone());
+            self.cache.lock().unwrap().save();
+        }
+
+        Ok(Some(hashes))
+    }
+
+    fn key_for_cache(filepath: &Path) -> Option<String> {
+        FrameHashCache::key_for(filepath)
+    }
+}
+
+impl Default for VideoFrameSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
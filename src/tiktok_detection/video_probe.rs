@@ -0,0 +1,215 @@
+use std::path::Path;
+use std::process::Command;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Metadata-atom values (`format.tags`/`stream.tags` in ffprobe's output —
+/// MP4/MOV's `udta`/`meta`/`ilst` boxes, covering encoder, handler, and
+/// `©xyz`-style author/comment atoms) that contain a recognizable TikTok
+/// fingerprint, the way czkawka's tag-based music dedup reads id3/mp4 tags.
+const TIKTOK_TAG_MARKERS: &[&str] = &["tiktok", "douyin", "bytedance", "musically", "musical.ly"];
+
+/// One MP4/MOV metadata atom whose value matched a `TIKTOK_TAG_MARKERS`
+/// fingerprint, e.g. `{atom: "encoder", value: "TikTok 29.1.3"}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mp4TagMatch {
+    pub atom: String,
+    pub value: String,
+}
+
+/// Genuine stream-level facts about a video, read from `ffprobe` rather than
+/// guessed from the container's first bytes the way `MetadataManager` does
+/// for images.
+#[derive(Debug, Clone)]
+pub struct VideoProbeResult {
+    /// True decoded frame width/height, already corrected for `rotation_degrees`
+    /// (a 90/270-degree rotated stream reports its *displayed* width/height).
+    pub width: u32,
+    pub height: u32,
+    pub codec_name: String,
+    pub container_format: String,
+    pub duration_secs: f64,
+    pub frame_count: Option<u64>,
+    pub rotation_degrees: i32,
+    /// Whether both the first and last frame could actually be decoded.
+    pub decodable: bool,
+}
+
+/// Probes videos with `ffprobe`/`ffmpeg` for their true codec, container,
+/// duration, and dimensions, and verifies decodability by reading the first
+/// and last frame. Degrades to `None` everywhere when neither binary is
+/// installed, so callers can fall back to their existing heuristics.
+pub struct VideoProbe {
+    ffprobe_available: bool,
+    ffmpeg_available: bool,
+}
+
+impl VideoProbe {
+    pub fn new() -> Self {
+        let ffprobe_available = Command::new("ffprobe")
+            .arg("-version")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+        let ffmpeg_available = Command::new("ffmpeg")
+            .arg("-version")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+
+        VideoProbe { ffprobe_available, ffmpeg_available }
+    }
+
+    /// Probes `filepath`, returning `None` if `ffprobe` isn't installed or
+    /// the file has no readable video stream.
+    pub fn probe(&self, filepath: &Path) -> Option<VideoProbeResult> {
+        if !self.ffprobe_available {
+            return None;
+        }
+
+        let output = Command::new("ffprobe")
+            .args([
+                "-v", "error",
+                "-select_streams", "v:0",
+                "-show_entries", "stream=width,height,codec_name,nb_frames:stream_side_data=rotation:stream_tags=rotate:format=duration,format_name",
+                "-of", "json",
+            ])
+            .arg(filepath)
+            .output()
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let parsed: Value = serde_json::from_slice(&output.stdout).ok()?;
+        let stream = parsed.get("streams")?.get(0)?;
+        let format = parsed.get("format");
+
+        let raw_width = stream.get("width")?.as_u64()? as u32;
+        let raw_height = stream.get("height")?.as_u64()? as u32;
+        let codec_name = stream.get("codec_name").and_then(Value::as_str).unwrap_or("unknown").to_string();
+        let frame_count = stream.get("nb_frames").and_then(Value::as_str).and_then(|s| s.parse().ok());
+
+        let rotation_degrees = Self::read_rotation(stream);
+        let (width, height) = if rotation_degrees % 180 != 0 {
+            (raw_height, raw_width)
+        } else {
+            (raw_width, raw_height)
+        };
+
+        let duration_secs = format
+            .and_then(|f| f.get("duration"))
+            .and_then(Value::as_str)
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0.0);
+        let container_format = format
+            .and_then(|f| f.get("format_name"))
+            .and_then(Value::as_str)
+            .unwrap_or("unknown")
+            .to_string();
+
+        let decodable = self.check_decodable(filepath, duration_secs);
+
+        Some(VideoProbeResult {
+            width,
+            height,
+            codec_name,
+            container_format,
+            duration_secs,
+            frame_count,
+            rotation_degrees,
+            decodable,
+        })
+    }
+
+    /// Reads every format- and stream-level metadata tag `ffprobe` can see
+    /// (MP4/MOV's `udta`/`meta`/`ilst` atoms: encoder, handler_name, the
+    /// `©xyz` author/comment family, `com.android.version`, ...) and returns
+    /// the first one whose value contains a `TIKTOK_TAG_MARKERS` fingerprint.
+    /// `None` if `ffprobe` is missing, the file has no tags, or none match.
+    pub fn find_tiktok_tag(&self, filepath: &Path) -> Option<Mp4TagMatch> {
+        if !self.ffprobe_available {
+            return None;
+        }
+
+        let output = Command::new("ffprobe")
+            .args(["-v", "error", "-show_entries", "format_tags:stream_tags", "-of", "json"])
+            .arg(filepath)
+            .output()
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let parsed: Value = serde_json::from_slice(&output.stdout).ok()?;
+        let mut tags: Vec<(String, String)> = Vec::new();
+
+        if let Some(format_tags) = parsed.get("format").and_then(|f| f.get("tags")).and_then(Value::as_object) {
+            tags.extend(format_tags.iter().filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string()))));
+        }
+        if let Some(streams) = parsed.get("streams").and_then(Value::as_array) {
+            for stream in streams {
+                if let Some(stream_tags) = stream.get("tags").and_then(Value::as_object) {
+                    tags.extend(stream_tags.iter().filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string()))));
+                }
+            }
+        }
+
+        tags.into_iter()
+            .find(|(_, value)| {
+                let lower = value.to_lowercase();
+                TIKTOK_TAG_MARKERS.iter().any(|marker| lower.contains(marker))
+            })
+            .map(|(atom, value)| Mp4TagMatch { atom, value })
+    }
+
+    /// Reads rotation from the modern `side_data_list` entry if present,
+    /// otherwise the legacy `rotate` tag, defaulting to 0.
+    fn read_rotation(stream: &Value) -> i32 {
+        if let Some(side_data) = stream.get("side_data_list").and_then(Value::as_array) {
+            for entry in side_data {
+                if let Some(rotation) = entry.get("rotation").and_then(Value::as_i64) {
+                    return rotation.rem_euclid(360) as i32;
+                }
+            }
+        }
+
+        stream.get("tags")
+            .and_then(|tags| tags.get("rotate"))
+            .and_then(Value::as_str)
+            .and_then(|s| s.parse::<i32>().ok())
+            .map(|r| r.rem_euclid(360))
+            .unwrap_or(0)
+    }
+
+    /// Attempts to decode the first and last frame of `filepath` (each via a
+    /// `-frames:v 1` seek, discarding the output to `-f null -`), verifying
+    /// the file isn't truncated or corrupt beyond what a stat-only check
+    /// could catch.
+    fn check_decodable(&self, filepath: &Path, duration_secs: f64) -> bool {
+        if !self.ffmpeg_available {
+            return false;
+        }
+
+        let last_frame_timestamp = (duration_secs - 0.1).max(0.0);
+        self.decodes_frame_at(filepath, 0.0) && self.decodes_frame_at(filepath, last_frame_timestamp)
+    }
+
+    fn decodes_frame_at(&self, filepath: &Path, timestamp_secs: f64) -> bool {
+        Command::new("ffmpeg")
+            .args(["-v", "error", "-ss", &format!("{:.3}", timestamp_secs), "-i"])
+            .arg(filepath)
+            .args(["-frames:v", "1", "-f", "null", "-"])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+}
+
+impl Default for VideoProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}